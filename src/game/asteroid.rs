@@ -0,0 +1,480 @@
+use super::{lerp_color, OnGameScreen};
+use crate::geometry::{self, GeometryError};
+use crate::GameState;
+
+use bevy::prelude::*;
+
+use bevy_prototype_lyon::{
+    prelude::{FillMode, *},
+    shapes::Polygon,
+};
+
+use bevy_rapier2d::prelude::*;
+
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+use std::ops::Range;
+
+pub const ASTEROID_COLOR: &str = "444444";
+const ASTEROID_HOT_COLOR: &str = "d4281c";
+const INTEGRITY_PER_AREA: f32 = 1.;
+// Speed, per unit of distance from the parent centroid, at which fragments fly apart.
+const FRAGMENT_EJECTION_RATE: f32 = 1.5;
+// Number of cracks radiating from the impact point with `Fracture::CutPlane`.
+const FRACTURE_CUTS: usize = 3;
+// Slivers thinner than this left over by a cut are dropped.
+const MIN_FRAGMENT_AREA: f32 = 1e-4;
+
+/// How asteroids break apart when their integrity is depleted.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum Fracture {
+    /// Triangles fanned around the centroid and paired up, wherever the beam hit.
+    Fan,
+    /// Cuts along lines radiating from the impact point.
+    #[default]
+    CutPlane,
+}
+
+/// Insert before `GamePlugin` to override the defaults.
+#[derive(Resource, Default)]
+pub struct AsteroidConfig {
+    pub fracture: Fracture,
+}
+
+#[derive(Component)]
+pub struct Asteroid;
+
+/// Size classes, as in the original Asteroids: few big slow rocks, many small ones.
+#[derive(Component, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AsteroidSize {
+    Large,
+    Medium,
+    Small,
+}
+
+impl AsteroidSize {
+    pub const ALL: [AsteroidSize; 3] = [
+        AsteroidSize::Large,
+        AsteroidSize::Medium,
+        AsteroidSize::Small,
+    ];
+
+    fn radius(&self) -> Range<f32> {
+        match self {
+            AsteroidSize::Large => 5.0..7.0,
+            AsteroidSize::Medium => 3.0..5.0,
+            AsteroidSize::Small => 1.5..3.0,
+        }
+    }
+
+    /// Big rocks are denser so they plough through the smaller ones.
+    pub fn density(&self) -> f32 {
+        match self {
+            AsteroidSize::Large => 14.,
+            AsteroidSize::Medium => 10.,
+            AsteroidSize::Small => 7.,
+        }
+    }
+
+    /// How far the outline strays from a circle, relative to the radius.
+    fn roughness(&self) -> f32 {
+        match self {
+            AsteroidSize::Large => 0.35,
+            AsteroidSize::Medium => 0.3,
+            AsteroidSize::Small => 0.2,
+        }
+    }
+
+    fn vertex_count(&self) -> usize {
+        match self {
+            AsteroidSize::Large => 20,
+            AsteroidSize::Medium => 16,
+            AsteroidSize::Small => 10,
+        }
+    }
+
+    /// Furthest an outline of this size can reach from its centre.
+    pub fn max_extent(&self) -> f32 {
+        self.radius().end * (1. + self.roughness())
+    }
+}
+
+/// How much beam exposure an asteroid withstands before shattering, proportional to its area.
+#[derive(Component, Debug)]
+pub struct Integrity {
+    pub current: f32,
+    pub max: f32,
+}
+
+impl Integrity {
+    pub fn from_area(area: f32) -> Self {
+        let max = area * INTEGRITY_PER_AREA;
+        Integrity { current: max, max }
+    }
+
+    pub fn fraction(&self) -> f32 {
+        if self.max > 0. {
+            (self.current / self.max).clamp(0., 1.)
+        } else {
+            0.
+        }
+    }
+
+    pub fn is_depleted(&self) -> bool {
+        self.current <= 0.
+    }
+}
+
+#[derive(Component)]
+pub struct Destructible(pub bool);
+
+/// Sent when the laser shatters an asteroid.
+pub struct AsteroidDestroyed {
+    pub area: f32,
+    pub position: Vec2,
+}
+
+/// Sent for every fragment too small to survive the shattering.
+pub struct AsteroidVaporised {
+    pub area: f32,
+    pub position: Vec2,
+}
+
+#[derive(SystemLabel, Debug, Clone, PartialEq, Eq, Hash)]
+pub enum AsteroidSystem {
+    Heat,
+}
+
+pub struct AsteroidPlugin;
+
+impl Plugin for AsteroidPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<AsteroidConfig>()
+            .add_event::<AsteroidDestroyed>()
+            .add_event::<AsteroidVaporised>()
+            .add_system_set(
+                SystemSet::on_update(GameState::Game)
+                    .with_system(asteroid_heat.label(AsteroidSystem::Heat)),
+            );
+    }
+}
+
+/// Asteroids glow from grey to red as the beam wears their integrity down.
+fn asteroid_heat(mut query: Query<(&Integrity, &mut DrawMode), Changed<Integrity>>) {
+    let cold = Color::hex(ASTEROID_COLOR).unwrap();
+    let hot = Color::hex(ASTEROID_HOT_COLOR).unwrap();
+    for (integrity, mut mode) in query.iter_mut() {
+        let color = lerp_color(cold, hot, 1. - integrity.fraction());
+        *mode = DrawMode::Fill(FillMode::color(color));
+    }
+}
+
+/// An asteroid outline, counter-clockwise and centred on its centroid, with the convex
+/// parts its collider is made of.
+#[derive(Component, Clone, Debug, PartialEq)]
+pub struct AsteroidShape {
+    pub vertices: Vec<Vec2>,
+    pub area: f32,
+    pub centroid: Vec2,
+    pub parts: Vec<Vec<Vec2>>,
+}
+
+impl AsteroidShape {
+    pub fn new(mut vertices: Vec<Vec2>) -> Result<Self, GeometryError> {
+        if geometry::signed_area(&vertices) < 0. {
+            vertices.reverse();
+        }
+        let centroid = geometry::centroid(&vertices)?;
+        let parts = geometry::convex_decomposition(&vertices)?;
+        Ok(AsteroidShape {
+            area: geometry::area(&vertices),
+            vertices,
+            centroid,
+            parts,
+        })
+    }
+
+    /// Craggy outline from radial noise: a few random harmonics for the overall lumpiness,
+    /// plus jitter on every vertex. Always star-shaped around the origin, so never
+    /// self-intersecting.
+    pub fn generate(seed: u64, size: AsteroidSize) -> Result<Self, GeometryError> {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let radius = rng.gen_range(size.radius());
+        let roughness = size.roughness();
+        let harmonics: Vec<(f32, f32, f32)> = (2..6)
+            .map(|frequency| {
+                (
+                    frequency as f32,
+                    rng.gen_range(0.0..roughness) / frequency as f32 * 2.,
+                    rng.gen_range(0.0..std::f32::consts::TAU),
+                )
+            })
+            .collect();
+
+        let count = size.vertex_count();
+        let step = std::f32::consts::TAU / count as f32;
+        let vertices: Vec<Vec2> = (0..count)
+            .map(|i| {
+                let angle = (i as f32 + rng.gen_range(-0.3..0.3)) * step;
+                let noise: f32 = harmonics
+                    .iter()
+                    .map(|(frequency, amplitude, phase)| {
+                        amplitude * (frequency * angle + phase).sin()
+                    })
+                    .sum();
+                let jitter = rng.gen_range(-roughness..roughness) * 0.5;
+                let scale = (1. + noise + jitter).clamp(1. - roughness * 1.5, 1. + roughness);
+                Vec2::from_angle(angle) * radius * scale
+            })
+            .collect();
+
+        let centroid = geometry::centroid(&vertices)?;
+        AsteroidShape::new(vertices.iter().map(|vertex| *vertex - centroid).collect())
+    }
+
+    /// Rapier dynamic bodies need convex shapes, so concave outlines get a compound collider.
+    pub fn collider(&self) -> Option<Collider> {
+        let mut parts: Vec<Collider> = self
+            .parts
+            .iter()
+            .filter_map(|part| Collider::convex_polyline(part.clone()))
+            .collect();
+        match parts.len() {
+            0 => None,
+            1 => parts.pop(),
+            _ => Some(Collider::compound(
+                parts
+                    .into_iter()
+                    .map(|part| (Vec2::ZERO, 0., part))
+                    .collect(),
+            )),
+        }
+    }
+}
+
+/// Triangles fanned around the centroid of every convex part, paired up into quads.
+pub fn subdivide(shape: &AsteroidShape) -> Vec<AsteroidShape> {
+    shape
+        .parts
+        .iter()
+        .filter_map(|part| Some((part, geometry::centroid(part).ok()?)))
+        .flat_map(|(part, centroid)| {
+            let part_area = geometry::area(part);
+            geometry::fan_triangles(part, centroid)
+                .chunks(2)
+                .filter_map(|chunk| {
+                    let points: Vec<Vec2> = chunk.iter().flatten().copied().collect();
+                    AsteroidShape::new(geometry::convex_hull(&points).ok()?).ok()
+                })
+                // A triangle's last pair of fan triangles covers the whole of it.
+                .filter(|fragment| fragment.area < part_area * 0.999)
+                .collect::<Vec<_>>()
+        })
+        .collect()
+}
+
+/// Splits the convex polygon with `cuts` lines through `hit_point`, at evenly spread angles
+/// with a random offset, so cracks radiate from where the beam hit.
+pub fn fracture(
+    vertices: &[Vec2],
+    hit_point: Vec2,
+    cuts: usize,
+    rng: &mut impl Rng,
+) -> Vec<Vec<Vec2>> {
+    let offset = rng.gen_range(0.0..std::f32::consts::PI);
+    let mut fragments = vec![vertices.to_vec()];
+    for i in 0..cuts {
+        let jitter = rng.gen_range(-0.2..0.2);
+        let angle = offset + (i as f32 + jitter) * std::f32::consts::PI / cuts as f32;
+        let direction = Vec2::new(angle.cos(), angle.sin());
+        fragments = fragments
+            .iter()
+            .flat_map(|fragment| {
+                let (left, right) = geometry::split(fragment, hit_point, direction);
+                [left, right]
+            })
+            .filter(|fragment| fragment.len() >= 3 && geometry::area(fragment) > MIN_FRAGMENT_AREA)
+            .collect();
+    }
+    fragments
+}
+
+/// Breaks the asteroid apart; `hit_point` is in the asteroid's local space.
+pub fn shatter(
+    shape: &AsteroidShape,
+    hit_point: Vec2,
+    fracture_mode: Fracture,
+    rng: &mut impl Rng,
+) -> Vec<AsteroidShape> {
+    match fracture_mode {
+        Fracture::Fan => subdivide(shape),
+        Fracture::CutPlane => {
+            // The same cracks run through every convex part.
+            let seed = rng.gen();
+            shape
+                .parts
+                .iter()
+                .flat_map(|part| {
+                    fracture(
+                        part,
+                        hit_point,
+                        FRACTURE_CUTS,
+                        &mut StdRng::seed_from_u64(seed),
+                    )
+                })
+                .filter_map(|fragment| AsteroidShape::new(fragment).ok())
+                .collect()
+        }
+    }
+}
+
+/// Velocity of a fragment centred on `fragment_center` once its parent breaks apart: the
+/// velocity of that point of the parent, plus an outward impulse proportional to the
+/// fragment's mass and its offset from the parent centroid. The mass-weighted offsets of
+/// all fragments cancel out, so the debris carries exactly the parent's momentum.
+pub fn fragment_velocity(
+    parent: &Velocity,
+    parent_center: Vec2,
+    fragment_center: Vec2,
+) -> Velocity {
+    let offset = fragment_center - parent_center;
+    Velocity {
+        linvel: parent.linvel + offset.perp() * parent.angvel + offset * FRAGMENT_EJECTION_RATE,
+        angvel: parent.angvel,
+    }
+}
+
+/// Spawns a freshly generated asteroid of the given size, drifting with `velocity`.
+/// Nothing is spawned if the generated outline turns out degenerate.
+pub fn spawn_asteroid(
+    commands: &mut Commands,
+    rng: &mut impl Rng,
+    size: AsteroidSize,
+    translation: Vec2,
+    velocity: Velocity,
+) -> Option<Entity> {
+    let shape = AsteroidShape::generate(rng.gen(), size).ok()?;
+    let collider = shape.collider()?;
+
+    let entity = commands.spawn((
+        Asteroid,
+        OnGameScreen,
+        RigidBody::Dynamic,
+        Destructible(true),
+        size,
+        Integrity::from_area(shape.area),
+        collider,
+        velocity,
+        ColliderMassProperties::Density(size.density()),
+        GeometryBuilder::build_as(
+            &Polygon {
+                points: shape.vertices.clone(),
+                closed: true,
+            },
+            DrawMode::Fill(FillMode::color(Color::hex(ASTEROID_COLOR).unwrap())),
+            Transform::from_translation(translation.extend(0.)),
+        ),
+        shape,
+    ));
+    Some(entity.id())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use proptest::prelude::*;
+
+    use rand::{rngs::StdRng, SeedableRng};
+
+    fn convex_polygon(points: &[(f32, f32)]) -> Option<Vec<Vec2>> {
+        let points: Vec<Vec2> = points.iter().map(|&(x, y)| Vec2::new(x, y)).collect();
+        let vertices = geometry::convex_hull(&points).ok()?;
+        (geometry::area(&vertices) > 1.).then_some(vertices)
+    }
+
+    proptest! {
+        #[test]
+        fn generated_asteroids_fit_their_size_class(
+            seed in any::<u64>(),
+            size in prop::sample::select(AsteroidSize::ALL.to_vec()),
+        ) {
+            let shape = AsteroidShape::generate(seed, size).unwrap();
+
+            prop_assert_eq!(&shape, &AsteroidShape::generate(seed, size).unwrap());
+            prop_assert!(shape.centroid.length() < 1e-3);
+            for vertex in &shape.vertices {
+                // Recentring on the centroid moves the outline a little off the origin.
+                prop_assert!(vertex.length() <= size.max_extent() * 1.2);
+            }
+            let parts_area: f32 = shape.parts.iter().map(|part| geometry::area(part)).sum();
+            prop_assert!((parts_area - shape.area).abs() <= shape.area * 1e-3);
+            prop_assert!(shape.collider().is_some());
+        }
+
+        #[test]
+        fn fragment_areas_sum_to_the_parent_area(
+            points in prop::collection::vec((-6.0f32..6.0, -6.0f32..6.0), 3..50),
+            vertex in any::<prop::sample::Index>(),
+            along_edge in 0.0f32..1.0,
+            towards_centroid in 0.0f32..1.0,
+            seed in any::<u64>(),
+        ) {
+            let Some(vertices) = convex_polygon(&points) else {
+                return Ok(());
+            };
+
+            // Anywhere from the hull's edge, where the ray hits, to its centroid.
+            let i = vertex.index(vertices.len());
+            let on_edge = vertices[i].lerp(vertices[(i + 1) % vertices.len()], along_edge);
+            let hit_point = on_edge.lerp(geometry::centroid(&vertices).unwrap(), towards_centroid);
+
+            let fragments = fracture(&vertices, hit_point, FRACTURE_CUTS, &mut StdRng::seed_from_u64(seed));
+
+            let parent_area = geometry::area(&vertices);
+            let fragments_area: f32 = fragments.iter().map(|fragment| geometry::area(fragment)).sum();
+            prop_assert!(fragments.len() >= 2);
+            prop_assert!(
+                (fragments_area - parent_area).abs() <= parent_area * 1e-3,
+                "fragments {} != parent {}",
+                fragments_area,
+                parent_area
+            );
+        }
+
+        #[test]
+        fn fragments_conserve_the_parent_momentum(
+            points in prop::collection::vec((-6.0f32..6.0, -6.0f32..6.0), 3..50),
+            linvel in (-10.0f32..10.0, -10.0f32..10.0),
+            angvel in -3.0f32..3.0,
+            seed in any::<u64>(),
+        ) {
+            let Some(vertices) = convex_polygon(&points) else {
+                return Ok(());
+            };
+            let parent = Velocity {
+                linvel: Vec2::new(linvel.0, linvel.1),
+                angvel,
+            };
+            let parent_center = geometry::centroid(&vertices).unwrap();
+            let fragments = fracture(&vertices, parent_center, FRACTURE_CUTS, &mut StdRng::seed_from_u64(seed));
+
+            // With a uniform density, mass is proportional to area.
+            let parent_area = geometry::area(&vertices);
+            let momentum: Vec2 = fragments
+                .iter()
+                .map(|fragment| {
+                    let velocity = fragment_velocity(&parent, parent_center, geometry::centroid(fragment).unwrap());
+                    velocity.linvel * geometry::area(fragment)
+                })
+                .sum();
+            let parent_momentum = parent.linvel * parent_area;
+            prop_assert!(
+                (momentum - parent_momentum).length() <= parent_area * 1e-2,
+                "fragments {} != parent {}",
+                momentum,
+                parent_momentum
+            );
+        }
+    }
+}
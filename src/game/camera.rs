@@ -0,0 +1,411 @@
+use super::asteroid::{Asteroid, AsteroidDestroyed};
+use super::comet::{CometDestroyed, CometSystem};
+use super::health::{HealthSystem, PlayerDamaged};
+use super::laser::LaserSystem;
+use super::monster::Monster;
+use super::player::{Player, PlayerSystem};
+use super::simulation::{SimulationApp, SimulationClock};
+use crate::{GameState, MainCamera};
+
+use bevy::prelude::*;
+
+use bevy_rapier2d::prelude::*;
+
+// A jump this far in a single step is a teleport (the arena wrapping around), which the
+// camera follows at once instead of sweeping across the whole arena.
+const TELEPORT_DISTANCE: f32 = 50.;
+
+// Trauma from each kind of impact.
+const FRACTURE_TRAUMA: f32 = 0.1;
+const FRACTURE_TRAUMA_PER_AREA: f32 = 0.005;
+const MAX_FRACTURE_TRAUMA: f32 = 0.4;
+const DAMAGE_TRAUMA: f32 = 0.2;
+const DAMAGE_TRAUMA_PER_HEALTH: f32 = 0.02;
+const COMET_TRAUMA: f32 = 1.;
+
+/// Insert before `GamePlugin` to override the defaults.
+#[derive(Resource, Clone, Debug)]
+pub struct CameraConfig {
+    /// Half size of the area around the centre of the screen the dino moves in freely,
+    /// in world units, before the camera starts following.
+    pub dead_zone: Vec2,
+    /// Roughly how many seconds the camera takes to catch up.
+    pub smoothing: f32,
+    /// How many seconds of the dino's velocity the camera looks ahead of it.
+    pub look_ahead: f32,
+    pub max_look_ahead: f32,
+    /// `OrthographicProjection::scale` when standing still with nothing around.
+    pub scale: f32,
+    pub max_scale: f32,
+    /// Zooms out by this fraction of `scale` per unit of speed...
+    pub zoom_per_speed: f32,
+    /// ...and per asteroid or monster within `threat_radius` of the dino.
+    pub zoom_per_threat: f32,
+    pub threat_radius: f32,
+    pub zoom_smoothing: f32,
+    /// Furthest the shake moves the camera, in world units, and turns it, in radians, at full
+    /// trauma.
+    pub max_shake_offset: f32,
+    pub max_shake_angle: f32,
+    pub shake_frequency: f32,
+    /// Trauma lost per second.
+    pub trauma_decay: f32,
+    /// Seconds the simulation slows down for after a big impact. No hit-stop when zero.
+    pub hit_stop: f32,
+    pub hit_stop_time_scale: f32,
+    /// Impacts adding at least this much trauma at once are big ones.
+    pub hit_stop_trauma: f32,
+}
+
+impl Default for CameraConfig {
+    fn default() -> Self {
+        CameraConfig {
+            dead_zone: Vec2::new(12., 8.),
+            smoothing: 0.3,
+            look_ahead: 0.5,
+            max_look_ahead: 20.,
+            scale: 0.1,
+            max_scale: 0.16,
+            zoom_per_speed: 0.01,
+            zoom_per_threat: 0.04,
+            threat_radius: 40.,
+            zoom_smoothing: 1.,
+            max_shake_offset: 2.,
+            max_shake_angle: 0.04,
+            shake_frequency: 25.,
+            trauma_decay: 1.2,
+            hit_stop: 0.12,
+            hit_stop_time_scale: 0.1,
+            hit_stop_trauma: 0.5,
+        }
+    }
+}
+
+/// Where the camera is heading and how fast it is getting there.
+#[derive(Resource, Default, Debug)]
+pub struct CameraRig {
+    /// Where the camera is, shake aside.
+    position: Vec2,
+    goal: Vec2,
+    velocity: Vec2,
+    zoom_velocity: f32,
+    snap: bool,
+}
+
+/// How shaken up the camera is, from 0 to 1. The shake grows with its square, so small
+/// knocks barely register while big ones rattle the screen.
+#[derive(Resource, Default, Debug)]
+pub struct CameraTrauma {
+    level: f32,
+}
+
+impl CameraTrauma {
+    pub fn add(&mut self, amount: f32) {
+        self.level = (self.level + amount).clamp(0., 1.);
+    }
+
+    pub fn level(&self) -> f32 {
+        self.level
+    }
+
+    pub fn shake(&self) -> f32 {
+        self.level * self.level
+    }
+
+    fn decay(&mut self, amount: f32) {
+        self.level = (self.level - amount).max(0.);
+    }
+}
+
+/// Seconds of slowed down physics left.
+#[derive(Resource, Default, Debug)]
+struct HitStop(f32);
+
+/// Smooth noise between -1 and 1; each channel gives an unrelated signal.
+pub fn shake_noise(t: f32, channel: u32) -> f32 {
+    let t = t + channel as f32 * 17.3;
+    (t.sin() * 0.5 + (t * 2.3 + 1.3).sin() * 0.3 + (t * 5.7 + 2.1).sin() * 0.2).clamp(-1., 1.)
+}
+
+/// Moves `current` towards `target` like a critically damped spring: as fast as possible
+/// without overshooting. `smoothing` is about the time it takes to get there.
+pub fn smooth_damp(current: f32, target: f32, velocity: &mut f32, smoothing: f32, dt: f32) -> f32 {
+    let omega = 2. / smoothing.max(1e-4);
+    let x = omega * dt;
+    let decay = 1. / (1. + x + 0.48 * x * x + 0.235 * x * x * x);
+    let change = current - target;
+    let temp = (*velocity + omega * change) * dt;
+    *velocity = (*velocity - omega * temp) * decay;
+    let next = target + (change + temp) * decay;
+
+    // Never overshoot the target.
+    if (target - current > 0.) == (next > target) {
+        *velocity = 0.;
+        target
+    } else {
+        next
+    }
+}
+
+pub fn smooth_damp_vec2(
+    current: Vec2,
+    target: Vec2,
+    velocity: &mut Vec2,
+    smoothing: f32,
+    dt: f32,
+) -> Vec2 {
+    Vec2::new(
+        smooth_damp(current.x, target.x, &mut velocity.x, smoothing, dt),
+        smooth_damp(current.y, target.y, &mut velocity.y, smoothing, dt),
+    )
+}
+
+/// Moves `goal` just enough for `target` to be back within `dead_zone` of it.
+pub fn dead_zone_goal(goal: Vec2, target: Vec2, dead_zone: Vec2) -> Vec2 {
+    let offset = target - goal;
+    goal + offset - offset.clamp(-dead_zone, dead_zone)
+}
+
+#[derive(SystemLabel, Debug, Clone, PartialEq, Eq, Hash)]
+pub enum CameraSystem {
+    Trauma,
+    Follow,
+}
+
+pub struct CameraPlugin;
+
+impl Plugin for CameraPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<CameraConfig>()
+            .init_resource::<CameraRig>()
+            .init_resource::<CameraTrauma>()
+            .init_resource::<HitStop>()
+            .add_system_set(
+                SystemSet::on_enter(GameState::Game)
+                    .with_system(reset_camera)
+                    .with_system(reset_hit_stop),
+            )
+            // Chunks are streamed around the camera, so it is simulated like the rest.
+            .add_simulation_system_set(
+                SystemSet::new()
+                    .with_system(
+                        trauma
+                            .label(CameraSystem::Trauma)
+                            .after(LaserSystem::Rays)
+                            .after(HealthSystem::Damage)
+                            .after(CometSystem::Hull),
+                    )
+                    .with_system(
+                        camera
+                            .label(CameraSystem::Follow)
+                            .after(PlayerSystem::Movement),
+                    ),
+            )
+            // The shake is only for show and keeps going in real time through hit-stop. It is
+            // taken off again before the simulation, which must not see it.
+            .add_system_set_to_stage(
+                CoreStage::PreUpdate,
+                SystemSet::on_update(GameState::Game).with_system(steady_camera),
+            )
+            .add_system_set(
+                SystemSet::on_update(GameState::Game)
+                    .with_system(hit_stop)
+                    .with_system(shake),
+            )
+            .add_system_set(SystemSet::on_exit(GameState::Game).with_system(steady_camera));
+    }
+}
+
+fn reset_camera(mut rig: ResMut<CameraRig>, mut trauma: ResMut<CameraTrauma>) {
+    *rig = CameraRig {
+        snap: true,
+        ..default()
+    };
+    *trauma = CameraTrauma::default();
+}
+
+/// Takes the shake off, for the simulation and for the ending, which is staged around the
+/// camera.
+fn steady_camera(rig: Res<CameraRig>, mut camera_query: Query<&mut Transform, With<MainCamera>>) {
+    for mut transform in camera_query.iter_mut() {
+        transform.translation = rig.position.extend(1.);
+        transform.rotation = Quat::IDENTITY;
+    }
+}
+
+fn reset_hit_stop(mut hit_stop: ResMut<HitStop>, mut clock: ResMut<SimulationClock>) {
+    hit_stop.0 = 0.;
+    clock.set_time_scale(1.);
+}
+
+fn trauma(
+    config: Res<CameraConfig>,
+    mut trauma: ResMut<CameraTrauma>,
+    mut hit_stop: ResMut<HitStop>,
+    mut asteroid_destroyed_events: EventReader<AsteroidDestroyed>,
+    mut damaged_events: EventReader<PlayerDamaged>,
+    mut comet_destroyed_events: EventReader<CometDestroyed>,
+) {
+    let impacts = asteroid_destroyed_events
+        .iter()
+        .map(|event| {
+            (FRACTURE_TRAUMA + event.area * FRACTURE_TRAUMA_PER_AREA).min(MAX_FRACTURE_TRAUMA)
+        })
+        .chain(
+            damaged_events
+                .iter()
+                .map(|event| DAMAGE_TRAUMA + event.amount * DAMAGE_TRAUMA_PER_HEALTH),
+        )
+        .chain(comet_destroyed_events.iter().map(|_| COMET_TRAUMA));
+
+    for amount in impacts {
+        trauma.add(amount);
+        if config.hit_stop > 0. && amount >= config.hit_stop_trauma {
+            hit_stop.0 = config.hit_stop;
+        }
+    }
+}
+
+fn hit_stop(
+    time: Res<Time>,
+    config: Res<CameraConfig>,
+    mut hit_stop: ResMut<HitStop>,
+    mut clock: ResMut<SimulationClock>,
+) {
+    if hit_stop.0 <= 0. {
+        return;
+    }
+    // Counted in real time, the simulation being the thing slowed down.
+    hit_stop.0 -= time.delta_seconds();
+    let scale = if hit_stop.0 > 0. {
+        config.hit_stop_time_scale
+    } else {
+        1.
+    };
+    clock.set_time_scale(scale);
+}
+
+fn camera(
+    clock: Res<SimulationClock>,
+    config: Res<CameraConfig>,
+    mut rig: ResMut<CameraRig>,
+    mut camera_query: Query<
+        (&mut Transform, &mut OrthographicProjection),
+        (With<MainCamera>, Without<Player>),
+    >,
+    player_query: Query<(&Transform, &Velocity), With<Player>>,
+    threat_query: Query<&Transform, (Or<(With<Asteroid>, With<Monster>)>, Without<MainCamera>)>,
+) {
+    let (Ok((mut camera_transform, mut projection)), Ok((player_transform, velocity))) =
+        (camera_query.get_single_mut(), player_query.get_single())
+    else {
+        return;
+    };
+    let dt = clock.delta_seconds();
+    let player_position = player_transform.translation.truncate();
+    let look_ahead = (velocity.linvel * config.look_ahead).clamp_length_max(config.max_look_ahead);
+    let target = player_position + look_ahead;
+
+    let position = rig.position;
+    let position =
+        if rig.snap || position.distance(target) > TELEPORT_DISTANCE + config.max_look_ahead {
+            rig.snap = false;
+            rig.goal = target;
+            rig.velocity = Vec2::ZERO;
+            target
+        } else {
+            rig.goal = dead_zone_goal(rig.goal, target, config.dead_zone);
+            let goal = rig.goal;
+            smooth_damp_vec2(position, goal, &mut rig.velocity, config.smoothing, dt)
+        };
+    rig.position = position;
+    camera_transform.translation = position.extend(1.);
+
+    let threats = threat_query
+        .iter()
+        .filter(|transform| {
+            transform.translation.truncate().distance(player_position) < config.threat_radius
+        })
+        .count();
+    let zoom = 1.
+        + velocity.linvel.length() * config.zoom_per_speed
+        + threats as f32 * config.zoom_per_threat;
+    let scale = (config.scale * zoom).min(config.max_scale);
+    projection.scale = smooth_damp(
+        projection.scale,
+        scale,
+        &mut rig.zoom_velocity,
+        config.zoom_smoothing,
+        dt,
+    );
+}
+
+/// Shakes the camera on top of where the simulation left it.
+fn shake(
+    time: Res<Time>,
+    config: Res<CameraConfig>,
+    rig: Res<CameraRig>,
+    mut trauma: ResMut<CameraTrauma>,
+    mut camera_query: Query<&mut Transform, With<MainCamera>>,
+) {
+    trauma.decay(config.trauma_decay * time.delta_seconds());
+    let shake = trauma.shake();
+    let t = time.elapsed_seconds() * config.shake_frequency;
+    let offset = Vec2::new(shake_noise(t, 0), shake_noise(t, 1)) * config.max_shake_offset * shake;
+    for mut transform in camera_query.iter_mut() {
+        transform.translation = (rig.position + offset).extend(1.);
+        transform.rotation =
+            Quat::from_rotation_z(shake_noise(t, 2) * config.max_shake_angle * shake);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use proptest::prelude::*;
+
+    proptest! {
+        #[test]
+        fn smoothing_converges_without_overshooting(
+            start in -100f32..100.,
+            target in -100f32..100.,
+            smoothing in 0.05f32..2.,
+        ) {
+            let mut velocity = 0.;
+            let mut current = start;
+            for _ in 0..1200 {
+                let next = smooth_damp(current, target, &mut velocity, smoothing, 1. / 60.);
+                prop_assert!((next - target).abs() <= (current - target).abs() + 1e-4);
+                current = next;
+            }
+            prop_assert!((current - target).abs() < 1e-2);
+        }
+    }
+
+    #[test]
+    fn trauma_shakes_quadratically_and_saturates() {
+        let mut trauma = CameraTrauma::default();
+        trauma.add(0.5);
+        assert_eq!(trauma.shake(), 0.25);
+
+        trauma.add(2.);
+        assert_eq!(trauma.level(), 1.);
+        trauma.decay(3.);
+        assert_eq!(trauma.shake(), 0.);
+    }
+
+    #[test]
+    fn dead_zone_holds_the_camera_still() {
+        let dead_zone = Vec2::new(10., 5.);
+
+        assert_eq!(
+            dead_zone_goal(Vec2::ZERO, Vec2::new(8., -4.), dead_zone),
+            Vec2::ZERO
+        );
+        assert_eq!(
+            dead_zone_goal(Vec2::ZERO, Vec2::new(15., -4.), dead_zone),
+            Vec2::new(5., 0.)
+        );
+    }
+}
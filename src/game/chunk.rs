@@ -0,0 +1,371 @@
+use super::asteroid::{spawn_asteroid, AsteroidSize};
+use super::simulation::{GameRng, SimulationApp, SimulationSystem};
+use super::wave::visible_half_extents;
+use super::wrap::WrapConfig;
+use crate::{GameState, MainCamera};
+
+use bevy::{math::Rect, prelude::*, utils::HashMap, utils::HashSet};
+
+use bevy_rapier2d::prelude::*;
+
+use rand::{rngs::StdRng, seq::SliceRandom, Rng, SeedableRng};
+
+use std::ops::Range;
+
+// Chunks past the edge of the screen that are loaded ahead of the camera, and the extra
+// distance a loaded chunk is kept for so that hovering over a border doesn't churn it.
+const LOAD_MARGIN: i32 = 1;
+const UNLOAD_MARGIN: i32 = 2;
+
+// Nothing is generated this close to the origin, where the player spawns.
+const SAFE_RADIUS: f32 = 20.;
+
+/// Insert before `GamePlugin` to override the defaults.
+#[derive(Resource, Clone, Debug)]
+pub struct ChunkConfig {
+    /// The world comes from the run's `GameRng` unless a seed is given.
+    pub seed: Option<u64>,
+    /// Side of a chunk, in world units.
+    pub size: f32,
+    pub asteroids: Range<u32>,
+    /// Relative odds of each size class.
+    pub sizes: Vec<(AsteroidSize, u32)>,
+    pub speed: Range<f32>,
+}
+
+impl Default for ChunkConfig {
+    fn default() -> Self {
+        ChunkConfig {
+            seed: None,
+            size: 40.,
+            asteroids: 0..4,
+            sizes: vec![
+                (AsteroidSize::Large, 2),
+                (AsteroidSize::Medium, 3),
+                (AsteroidSize::Small, 3),
+            ],
+            speed: 0.0..0.5,
+        }
+    }
+}
+
+/// Which chunk an asteroid was generated for, and its place among the chunk's asteroids.
+#[derive(Component, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct ChunkAsteroid {
+    pub chunk: IVec2,
+    pub index: u32,
+}
+
+/// An asteroid a chunk starts with.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ChunkContent {
+    pub index: u32,
+    pub size: AsteroidSize,
+    pub position: Vec2,
+    pub velocity: Vec2,
+    pub angular_velocity: f32,
+    /// Seeds the asteroid's outline.
+    pub seed: u64,
+}
+
+/// The chunks around the camera, and what was destroyed in the ones already visited.
+#[derive(Resource, Default)]
+pub struct ChunkMap {
+    seed: u64,
+    loaded: HashMap<IVec2, Vec<Entity>>,
+    /// Asteroids of the loaded chunks, until they are destroyed or unloaded.
+    asteroids: HashMap<Entity, ChunkAsteroid>,
+    destroyed: HashSet<ChunkAsteroid>,
+}
+
+impl ChunkMap {
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    pub fn is_destroyed(&self, asteroid: &ChunkAsteroid) -> bool {
+        self.destroyed.contains(asteroid)
+    }
+}
+
+pub fn chunk_coord(position: Vec2, size: f32) -> IVec2 {
+    (position / size).floor().as_ivec2()
+}
+
+/// Mixes the world seed with the chunk coordinates (splitmix64), so neighbouring chunks get
+/// unrelated content.
+fn chunk_seed(seed: u64, chunk: IVec2) -> u64 {
+    let mut z = seed
+        ^ (chunk.x as u32 as u64).wrapping_mul(0x9e37_79b9_7f4a_7c15)
+        ^ (chunk.y as u32 as u64)
+            .wrapping_mul(0xc2b2_ae3d_27d4_eb4f)
+            .rotate_left(32);
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
+
+/// The asteroids of a chunk, always the same for a given seed.
+pub fn chunk_content(seed: u64, chunk: IVec2, config: &ChunkConfig) -> Vec<ChunkContent> {
+    let mut rng = StdRng::seed_from_u64(chunk_seed(seed, chunk));
+    let origin = chunk.as_vec2() * config.size;
+    let count = if config.asteroids.is_empty() {
+        0
+    } else {
+        rng.gen_range(config.asteroids.clone())
+    };
+
+    let mut content = Vec::new();
+    for index in 0..count {
+        // Drawn even for rejected asteroids, so the others don't depend on what got rejected.
+        let size = config
+            .sizes
+            .choose_weighted(&mut rng, |(_, weight)| *weight)
+            .map(|(size, _)| *size)
+            .unwrap_or(AsteroidSize::Medium);
+        let position =
+            origin + Vec2::new(rng.gen_range(0.05..0.95), rng.gen_range(0.05..0.95)) * config.size;
+        let speed = if config.speed.is_empty() {
+            config.speed.start
+        } else {
+            rng.gen_range(config.speed.clone())
+        };
+        let velocity = Vec2::from_angle(rng.gen_range(0.0..std::f32::consts::TAU)) * speed;
+        let angular_velocity = rng.gen_range(-0.3..0.3);
+        let seed = rng.gen();
+
+        if position.length() < SAFE_RADIUS + size.max_extent() {
+            continue;
+        }
+        content.push(ChunkContent {
+            index,
+            size,
+            position,
+            velocity,
+            angular_velocity,
+            seed,
+        });
+    }
+    content
+}
+
+#[derive(SystemLabel, Debug, Clone, PartialEq, Eq, Hash)]
+pub enum ChunkSystem {
+    Stream,
+}
+
+pub struct ChunkPlugin;
+
+impl Plugin for ChunkPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ChunkConfig>()
+            .init_resource::<ChunkMap>()
+            .add_system_set(
+                SystemSet::on_enter(GameState::Game)
+                    .with_system(reset_chunks.after(SimulationSystem::Reset)),
+            )
+            .add_simulation_system_set(
+                SystemSet::new().with_system(stream_chunks.label(ChunkSystem::Stream)),
+            )
+            // Despawns by commands are only seen as removals after the stage they ran in.
+            .add_system_to_stage(CoreStage::PostUpdate, remember_destroyed);
+    }
+}
+
+/// The previous run's entities are gone with the game screen.
+fn reset_chunks(config: Res<ChunkConfig>, mut rng: ResMut<GameRng>, mut map: ResMut<ChunkMap>) {
+    *map = ChunkMap {
+        seed: config.seed.unwrap_or_else(|| rng.gen()),
+        ..default()
+    };
+}
+
+fn stream_chunks(
+    mut commands: Commands,
+    config: Res<ChunkConfig>,
+    wrap_config: Res<WrapConfig>,
+    mut map: ResMut<ChunkMap>,
+    camera_query: Query<(&Transform, &OrthographicProjection), With<MainCamera>>,
+) {
+    let Ok((camera_transform, projection)) = camera_query.get_single() else {
+        return;
+    };
+    let center = camera_transform.translation.truncate();
+    let camera_chunk = chunk_coord(center, config.size);
+    let reach = (visible_half_extents(projection) / config.size)
+        .ceil()
+        .as_ivec2();
+    let load_reach = reach + IVec2::splat(LOAD_MARGIN);
+    let unload_reach = reach + IVec2::splat(UNLOAD_MARGIN);
+
+    let mut far: Vec<IVec2> = map
+        .loaded
+        .keys()
+        .filter(|chunk| {
+            let offset = (**chunk - camera_chunk).abs();
+            offset.x > unload_reach.x || offset.y > unload_reach.y
+        })
+        .copied()
+        .collect();
+    // The map's order changes from run to run, and the despawn order decides which entities
+    // and physics handles get reused, which must not.
+    far.sort_unstable_by_key(|chunk| (chunk.y, chunk.x));
+    for chunk in far {
+        for entity in map.loaded.remove(&chunk).unwrap_or_default() {
+            // Already despawned if the asteroid was destroyed.
+            if map.asteroids.remove(&entity).is_some() {
+                commands.entity(entity).despawn();
+            }
+        }
+    }
+
+    for y in -load_reach.y..=load_reach.y {
+        for x in -load_reach.x..=load_reach.x {
+            let chunk = camera_chunk + IVec2::new(x, y);
+            if map.loaded.contains_key(&chunk) {
+                continue;
+            }
+            // A wrapping arena only has the chunks it overlaps.
+            let bounds = Rect::from_corners(
+                chunk.as_vec2() * config.size,
+                (chunk + IVec2::ONE).as_vec2() * config.size,
+            );
+            if let Some(arena) = wrap_config.arena {
+                if arena.intersect(bounds).is_empty() {
+                    continue;
+                }
+            }
+
+            let mut entities = Vec::new();
+            for content in chunk_content(map.seed, chunk, &config) {
+                let asteroid = ChunkAsteroid {
+                    chunk,
+                    index: content.index,
+                };
+                if map.is_destroyed(&asteroid) {
+                    continue;
+                }
+                let velocity = Velocity {
+                    linvel: content.velocity,
+                    angvel: content.angular_velocity,
+                };
+                let mut rng = StdRng::seed_from_u64(content.seed);
+                let Some(entity) = spawn_asteroid(
+                    &mut commands,
+                    &mut rng,
+                    content.size,
+                    content.position,
+                    velocity,
+                ) else {
+                    continue;
+                };
+                commands.entity(entity).insert(asteroid);
+                map.asteroids.insert(entity, asteroid);
+                entities.push(entity);
+            }
+            map.loaded.insert(chunk, entities);
+        }
+    }
+}
+
+/// Chunk asteroids that disappear while their chunk is loaded were shattered.
+fn remember_destroyed(mut map: ResMut<ChunkMap>, removed: RemovedComponents<ChunkAsteroid>) {
+    for entity in removed.iter() {
+        if let Some(asteroid) = map.asteroids.remove(&entity) {
+            map.destroyed.insert(asteroid);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::simulation::testing::{headless_app, play, start_game};
+    use super::*;
+
+    use proptest::prelude::*;
+
+    fn streaming_game(seed: u64) -> App {
+        let mut app = headless_app(seed);
+        app.insert_resource(ChunkConfig {
+            seed: Some(seed),
+            asteroids: 2..4,
+            ..default()
+        })
+        .init_resource::<WrapConfig>()
+        .add_plugin(ChunkPlugin);
+        app.world.spawn((
+            MainCamera,
+            Transform::default(),
+            OrthographicProjection {
+                scale: 40.,
+                ..default()
+            },
+        ));
+        start_game(&mut app);
+        app
+    }
+
+    fn move_camera(app: &mut App, position: Vec2) {
+        let mut query = app
+            .world
+            .query_filtered::<&mut Transform, With<MainCamera>>();
+        query.single_mut(&mut app.world).translation = position.extend(0.);
+    }
+
+    /// Flies out far enough for every chunk around the start to be unloaded, then back.
+    fn round_trip(seed: u64) -> Vec<(Entity, ChunkAsteroid, Vec2)> {
+        let mut app = streaming_game(seed);
+        for position in [Vec2::ZERO, Vec2::new(600., 200.), Vec2::ZERO] {
+            move_camera(&mut app, position);
+            play(&mut app, 30, 2);
+        }
+
+        let mut query = app.world.query::<(Entity, &ChunkAsteroid, &Transform)>();
+        let mut asteroids: Vec<_> = query
+            .iter(&app.world)
+            .map(|(entity, asteroid, transform)| {
+                (entity, *asteroid, transform.translation.truncate())
+            })
+            .collect();
+        asteroids.sort_unstable_by_key(|(_, asteroid, _)| {
+            (asteroid.chunk.y, asteroid.chunk.x, asteroid.index)
+        });
+        asteroids
+    }
+
+    proptest! {
+        #[test]
+        fn chunk_content_is_deterministic(seed: u64, x in -1000..1000i32, y in -1000..1000i32) {
+            let config = ChunkConfig::default();
+            let chunk = IVec2::new(x, y);
+            let content = chunk_content(seed, chunk, &config);
+
+            prop_assert_eq!(&content, &chunk_content(seed, chunk, &config));
+            for asteroid in &content {
+                prop_assert_eq!(chunk_coord(asteroid.position, config.size), chunk);
+                prop_assert!(asteroid.position.length() >= SAFE_RADIUS);
+            }
+        }
+    }
+
+    #[test]
+    fn chunks_differ_from_their_neighbours() {
+        let config = ChunkConfig {
+            asteroids: 3..4,
+            ..default()
+        };
+        let content = chunk_content(7, IVec2::new(4, 4), &config);
+
+        assert_ne!(content, chunk_content(7, IVec2::new(5, 4), &config));
+        assert_ne!(content, chunk_content(7, IVec2::new(4, 5), &config));
+        assert_ne!(content, chunk_content(8, IVec2::new(4, 4), &config));
+    }
+
+    #[test]
+    fn streaming_out_and_back_is_deterministic() {
+        let asteroids = round_trip(11);
+
+        assert!(!asteroids.is_empty());
+        assert_eq!(asteroids, round_trip(11));
+    }
+}
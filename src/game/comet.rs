@@ -0,0 +1,302 @@
+use super::asteroid::{spawn_asteroid, AsteroidShape, AsteroidSize};
+use super::health::Health;
+use super::laser::LaserSystem;
+use super::particle::{Particle, JETPACK_PARTICLE_LIFETIME};
+use super::player::Player;
+use super::simulation::{GameRng, SimulationApp, SimulationSystem};
+use super::OnGameScreen;
+use crate::{GameState, Transition};
+
+use bevy::prelude::*;
+
+use bevy_prototype_lyon::{
+    prelude::{FillMode, *},
+    shapes::Polygon,
+};
+
+use bevy_rapier2d::prelude::*;
+
+use rand::{seq::SliceRandom, Rng};
+
+/// Distance from the comet to Earth when each cycle starts, in km. Once the last comet is
+/// destroyed there is no time left for another one.
+const EARTH_DISTANCES: [f32; 3] = [1_500_000., 600_000., 150_000.];
+
+const COMET_COLOR: &str = "c8e6ff";
+const COMET_TAIL_COLORS: [&str; 3] = ["ffffff", "9ad9ff", "4fb3ff"];
+// The comet is drawn as a large asteroid outline scaled up by this much.
+const COMET_SCALE: f32 = 3.;
+const COMET_SPAWN_DISTANCE: f32 = 250.;
+// Distance to the comet at which the player counts as arrived.
+const COMET_REACH: f32 = 40.;
+const COMET_SPEED: f32 = 1.5;
+const COMET_HULL: f32 = 600.;
+// The hull sheds a burst of debris every time it loses this fraction of its strength.
+const COMET_DEBRIS_STEP: f32 = 0.25;
+const COMET_DEBRIS_COUNT: u32 = 4;
+
+/// Progress towards the end of the world, carried over from comet to comet.
+#[derive(Resource, Default, Debug)]
+pub struct Expedition {
+    cycle: usize,
+    approach: f32,
+    hull: f32,
+}
+
+impl Expedition {
+    /// Comets destroyed so far.
+    pub fn cycle(&self) -> usize {
+        self.cycle
+    }
+
+    pub fn distance_to_earth(&self) -> f32 {
+        EARTH_DISTANCES.get(self.cycle).copied().unwrap_or(0.)
+    }
+
+    /// How far through the current comet the player is: reaching it is the first half,
+    /// breaking its hull the second.
+    pub fn progress(&self) -> f32 {
+        (self.approach + (1. - self.hull)) / 2.
+    }
+
+    /// The last comet is destroyed, and the run can only end with it hitting Earth.
+    pub fn is_over(&self) -> bool {
+        self.cycle >= EARTH_DISTANCES.len()
+    }
+}
+
+#[derive(Component)]
+pub struct Comet {
+    start_distance: f32,
+    debris_shed: u32,
+}
+
+/// Sent when the laser breaks a comet's hull.
+pub struct CometDestroyed {
+    pub position: Vec2,
+}
+
+#[derive(SystemLabel, Debug, Clone, PartialEq, Eq, Hash)]
+pub enum CometSystem {
+    Progress,
+    Hull,
+}
+
+pub struct CometPlugin;
+
+impl Plugin for CometPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Expedition>()
+            .add_event::<CometDestroyed>()
+            .add_system_set(
+                SystemSet::on_enter(GameState::Game)
+                    .with_system(reset_expedition)
+                    .with_system(first_comet.after(SimulationSystem::Reset)),
+            )
+            .add_simulation_system_set(
+                SystemSet::new()
+                    .with_system(comet_progress.label(CometSystem::Progress))
+                    .with_system(comet_tail)
+                    .with_system(comet_hull.label(CometSystem::Hull).after(LaserSystem::Rays)),
+            );
+    }
+}
+
+fn reset_expedition(mut expedition: ResMut<Expedition>) {
+    *expedition = Expedition {
+        hull: 1.,
+        ..default()
+    };
+}
+
+fn first_comet(mut commands: Commands, mut rng: ResMut<GameRng>) {
+    // The player starts around the origin.
+    spawn_comet(&mut commands, &mut *rng, 0, Vec2::ZERO);
+}
+
+/// Every comet is further away, faster and tougher than the one before.
+fn spawn_comet(commands: &mut Commands, rng: &mut impl Rng, cycle: usize, from: Vec2) {
+    let difficulty = 1. + cycle as f32 * 0.5;
+    let start_distance = COMET_SPAWN_DISTANCE * (1. + cycle as f32 * 0.2);
+    let position =
+        from + Vec2::from_angle(rng.gen_range(0.0..std::f32::consts::TAU)) * start_distance;
+
+    let Some(shape) = AsteroidShape::generate(rng.gen(), AsteroidSize::Large)
+        .and_then(|shape| {
+            AsteroidShape::new(
+                shape
+                    .vertices
+                    .iter()
+                    .map(|vertex| *vertex * COMET_SCALE)
+                    .collect(),
+            )
+        })
+        .ok()
+    else {
+        return;
+    };
+    let Some(collider) = shape.collider() else {
+        return;
+    };
+
+    commands.spawn((
+        Comet {
+            start_distance,
+            debris_shed: 0,
+        },
+        OnGameScreen,
+        RigidBody::KinematicVelocityBased,
+        collider,
+        Velocity {
+            linvel: Vec2::from_angle(rng.gen_range(0.0..std::f32::consts::TAU))
+                * COMET_SPEED
+                * difficulty,
+            angvel: rng.gen_range(-0.1..0.1),
+        },
+        Health::new(COMET_HULL * difficulty),
+        GeometryBuilder::build_as(
+            &Polygon {
+                points: shape.vertices,
+                closed: true,
+            },
+            DrawMode::Fill(FillMode::color(Color::hex(COMET_COLOR).unwrap())),
+            Transform::from_translation(position.extend(0.)),
+        ),
+    ));
+}
+
+fn comet_radius() -> f32 {
+    AsteroidSize::Large.max_extent() * COMET_SCALE
+}
+
+fn comet_progress(
+    mut expedition: ResMut<Expedition>,
+    comet_query: Query<(&Comet, &Health, &Transform)>,
+    player_query: Query<&Transform, With<Player>>,
+) {
+    let (Ok((comet, health, comet_transform)), Ok(player_transform)) =
+        (comet_query.get_single(), player_query.get_single())
+    else {
+        return;
+    };
+    let distance = comet_transform
+        .translation
+        .truncate()
+        .distance(player_transform.translation.truncate());
+    let approach = 1. - (distance - COMET_REACH) / (comet.start_distance - COMET_REACH);
+
+    // Once reached, the comet stays reached even if the player backs off.
+    expedition.approach = expedition.approach.max(approach.clamp(0., 1.));
+    expedition.hull = health.fraction().max(0.);
+}
+
+fn comet_tail(
+    mut commands: Commands,
+    mut rng: ResMut<GameRng>,
+    comet_query: Query<(&Transform, &Velocity), With<Comet>>,
+) {
+    for (transform, velocity) in comet_query.iter() {
+        let backwards = -velocity.linvel.normalize_or_zero();
+        let sideways = backwards.perp() * rng.gen_range(-0.6..0.6) * comet_radius();
+        let color = Color::hex(COMET_TAIL_COLORS.choose(&mut *rng).unwrap()).unwrap();
+        commands.spawn((
+            Particle::new(backwards * 6., JETPACK_PARTICLE_LIFETIME),
+            OnGameScreen,
+            GeometryBuilder::build_as(
+                &shapes::Circle {
+                    radius: rng.gen_range(0.5..1.5),
+                    center: Vec2::ZERO,
+                },
+                DrawMode::Fill(FillMode::color(color)),
+                Transform::from_translation(
+                    (transform.translation.truncate() + sideways).extend(-0.1),
+                ),
+            ),
+        ));
+    }
+}
+
+fn comet_hull(
+    mut commands: Commands,
+    mut rng: ResMut<GameRng>,
+    mut transition: ResMut<Transition>,
+    mut expedition: ResMut<Expedition>,
+    mut comet_query: Query<(Entity, &mut Comet, &Health, &Transform, &Velocity), Changed<Health>>,
+    mut comet_destroyed_events: EventWriter<CometDestroyed>,
+) {
+    for (entity, mut comet, health, transform, velocity) in comet_query.iter_mut() {
+        let position = transform.translation.truncate();
+        let mut shed_debris = |count: u32| {
+            for _ in 0..count {
+                let direction = Vec2::from_angle(rng.gen_range(0.0..std::f32::consts::TAU));
+                let size = if rng.gen_bool(0.5) {
+                    AsteroidSize::Medium
+                } else {
+                    AsteroidSize::Small
+                };
+                let debris_velocity = Velocity {
+                    linvel: velocity.linvel + direction * rng.gen_range(3.0..8.0),
+                    angvel: rng.gen_range(-1.0..1.0),
+                };
+                spawn_asteroid(
+                    &mut commands,
+                    &mut *rng,
+                    size,
+                    position + direction * (comet_radius() + size.max_extent()),
+                    debris_velocity,
+                );
+            }
+        };
+
+        let shed = ((1. - health.fraction()) / COMET_DEBRIS_STEP) as u32;
+        if shed > comet.debris_shed {
+            shed_debris(COMET_DEBRIS_COUNT * (shed - comet.debris_shed));
+            comet.debris_shed = shed;
+        }
+
+        if !health.is_dead() {
+            continue;
+        }
+        commands.entity(entity).despawn();
+        comet_destroyed_events.send(CometDestroyed { position });
+
+        expedition.cycle += 1;
+        expedition.approach = 0.;
+        expedition.hull = 1.;
+        if expedition.is_over() {
+            // Nothing can stop the next one, not even the player dying during the fade.
+            transition.to_state = Some(GameState::Ending);
+            transition.value = 0.;
+            transition.step = 0.01;
+            transition.is_playing = true;
+        } else {
+            spawn_comet(&mut commands, &mut *rng, expedition.cycle, position);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn earth_gets_closer_with_every_comet() {
+        let mut expedition = Expedition {
+            hull: 1.,
+            ..default()
+        };
+        assert_eq!(expedition.progress(), 0.);
+
+        expedition.approach = 1.;
+        expedition.hull = 0.5;
+        assert_eq!(expedition.progress(), 0.75);
+
+        let mut distances = Vec::new();
+        while !expedition.is_over() {
+            distances.push(expedition.distance_to_earth());
+            expedition.cycle += 1;
+        }
+        assert!(distances.windows(2).all(|pair| pair[1] < pair[0]));
+        assert_eq!(expedition.distance_to_earth(), 0.);
+    }
+}
@@ -0,0 +1,285 @@
+use super::asteroid::{AsteroidShape, AsteroidSize};
+use super::simulation::GameRng;
+use crate::input::{Action, ActionState};
+use crate::{despawn_screen, GameState, MainCamera, Transition, FONT};
+
+use bevy::prelude::*;
+
+use bevy_prototype_lyon::{
+    prelude::{FillMode, *},
+    shapes::Polygon,
+};
+
+use rand::Rng;
+
+const EARTH_RADIUS: f32 = 12.;
+const EARTH_OFFSET: Vec2 = Vec2::new(20., -5.);
+const EARTH_COLOR: &str = "2f6fd6";
+const EARTH_BURNT_COLOR: &str = "d4281c";
+const LAND_COLOR: &str = "26b24a";
+const COMET_COLOR: &str = "c8e6ff";
+const COMET_TAIL_COLOR: &str = "9ad9ff66";
+const COMET_START_OFFSET: Vec2 = Vec2::new(-90., 45.);
+
+// Seconds into the ending at which each beat happens.
+const IMPACT_TIME: f32 = 3.;
+const FLASH_DURATION: f32 = 1.5;
+const TEXT_TIME: f32 = 5.;
+const PROMPT_TIME: f32 = 7.;
+
+const FLASH_MAX_RADIUS: f32 = 120.;
+
+#[derive(Component)]
+struct OnEndingScreen;
+
+#[derive(Component)]
+struct Earth;
+
+#[derive(Component)]
+struct EndingComet {
+    from: Vec2,
+    to: Vec2,
+}
+
+#[derive(Component)]
+struct Flash;
+
+/// Shown once the dust settles, the second one only after `PROMPT_TIME`.
+#[derive(Component)]
+struct EndingText(f32);
+
+/// Seconds since the ending started.
+#[derive(Resource, Default)]
+struct EndingClock(f32);
+
+pub struct EndingPlugin;
+
+impl Plugin for EndingPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<EndingClock>()
+            .add_system_set(
+                SystemSet::on_enter(GameState::Ending)
+                    .with_system(transition)
+                    .with_system(setup_ending),
+            )
+            .add_system_set(
+                SystemSet::on_update(GameState::Ending)
+                    .with_system(crash)
+                    .with_system(ending_text)
+                    .with_system(game_over),
+            )
+            .add_system_set(
+                SystemSet::on_exit(GameState::Ending).with_system(despawn_screen::<OnEndingScreen>),
+            );
+    }
+}
+
+fn transition(mut transition: ResMut<Transition>) {
+    transition.to_state = None;
+    transition.step = -0.01;
+    transition.is_playing = true;
+}
+
+fn setup_ending(
+    mut commands: Commands,
+    mut clock: ResMut<EndingClock>,
+    mut rng: ResMut<GameRng>,
+    asset_server: Res<AssetServer>,
+    camera_query: Query<&Transform, With<MainCamera>>,
+) {
+    clock.0 = 0.;
+    let center = camera_query
+        .get_single()
+        .map(|transform| transform.translation.truncate())
+        .unwrap_or_default();
+    let earth_position = center + EARTH_OFFSET;
+
+    commands
+        .spawn((
+            OnEndingScreen,
+            Earth,
+            GeometryBuilder::build_as(
+                &shapes::Circle {
+                    radius: EARTH_RADIUS,
+                    center: Vec2::ZERO,
+                },
+                DrawMode::Fill(FillMode::color(Color::hex(EARTH_COLOR).unwrap())),
+                Transform::from_translation(earth_position.extend(0.)),
+            ),
+        ))
+        .with_children(|parent| {
+            // A few continents, kept well inside the ocean.
+            for _ in 0..4 {
+                let Ok(land) = AsteroidShape::generate(rng.gen(), AsteroidSize::Small) else {
+                    continue;
+                };
+                let offset = Vec2::from_angle(rng.gen_range(0.0..std::f32::consts::TAU))
+                    * rng.gen_range(0.0..EARTH_RADIUS * 0.5);
+                parent.spawn(GeometryBuilder::build_as(
+                    &Polygon {
+                        points: land.vertices,
+                        closed: true,
+                    },
+                    DrawMode::Fill(FillMode::color(Color::hex(LAND_COLOR).unwrap())),
+                    Transform::from_translation(offset.extend(0.1)),
+                ));
+            }
+        });
+
+    let from = center + COMET_START_OFFSET;
+    let direction = (earth_position - from).normalize();
+    if let Ok(shape) = AsteroidShape::generate(rng.gen(), AsteroidSize::Medium) {
+        commands
+            .spawn((
+                OnEndingScreen,
+                EndingComet {
+                    from,
+                    to: earth_position,
+                },
+                GeometryBuilder::build_as(
+                    &Polygon {
+                        points: shape.vertices,
+                        closed: true,
+                    },
+                    DrawMode::Fill(FillMode::color(Color::hex(COMET_COLOR).unwrap())),
+                    Transform::from_translation(from.extend(0.2)),
+                ),
+            ))
+            .with_children(|parent| {
+                for (length, width) in [(40., 3.), (28., 5.), (18., 7.)] {
+                    parent.spawn(GeometryBuilder::build_as(
+                        &shapes::Line(Vec2::ZERO, -direction * length),
+                        DrawMode::Stroke(StrokeMode::new(
+                            Color::hex(COMET_TAIL_COLOR).unwrap(),
+                            width,
+                        )),
+                        Transform::from_xyz(0., 0., -0.1),
+                    ));
+                }
+            });
+    }
+
+    let text_style = TextStyle {
+        font: asset_server.load(FONT),
+        font_size: 30.,
+        color: Color::WHITE,
+    };
+    commands
+        .spawn((
+            OnEndingScreen,
+            NodeBundle {
+                style: Style {
+                    size: Size::new(Val::Percent(100.), Val::Percent(100.)),
+                    flex_direction: FlexDirection::Column,
+                    justify_content: JustifyContent::FlexEnd,
+                    align_items: AlignItems::Center,
+                    padding: UiRect::bottom(Val::Px(60.)),
+                    ..default()
+                },
+                ..default()
+            },
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                EndingText(TEXT_TIME),
+                TextBundle {
+                    visibility: Visibility::INVISIBLE,
+                    ..TextBundle::from_section(
+                        "...and that is how the dinosaurs disappeared",
+                        text_style.clone(),
+                    )
+                },
+            ));
+            parent.spawn((
+                EndingText(PROMPT_TIME),
+                TextBundle {
+                    visibility: Visibility::INVISIBLE,
+                    ..TextBundle::from_section(
+                        "Confirm to continue",
+                        TextStyle {
+                            font_size: 20.,
+                            ..text_style
+                        },
+                    )
+                    .with_style(Style {
+                        margin: UiRect::top(Val::Px(20.)),
+                        ..default()
+                    })
+                },
+            ));
+        });
+}
+
+fn crash(
+    time: Res<Time>,
+    mut commands: Commands,
+    mut clock: ResMut<EndingClock>,
+    mut comet_query: Query<(Entity, &EndingComet, &mut Transform)>,
+    mut earth_query: Query<(&Transform, &mut DrawMode), (With<Earth>, Without<EndingComet>)>,
+    mut flash_query: Query<
+        (&mut Transform, &mut DrawMode),
+        (With<Flash>, Without<Earth>, Without<EndingComet>),
+    >,
+) {
+    let previous = clock.0;
+    clock.0 += time.delta_seconds();
+    let elapsed = clock.0;
+
+    for (entity, comet, mut transform) in comet_query.iter_mut() {
+        if elapsed < IMPACT_TIME {
+            // Eased in, so it looks like it falls into Earth's gravity well.
+            let t = (elapsed / IMPACT_TIME).powi(3);
+            let position = comet.from.lerp(comet.to, t);
+            transform.translation = position.extend(transform.translation.z);
+        } else {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+
+    let Ok((earth_transform, mut earth_mode)) = earth_query.get_single_mut() else {
+        return;
+    };
+    if previous < IMPACT_TIME && elapsed >= IMPACT_TIME {
+        *earth_mode = DrawMode::Fill(FillMode::color(Color::hex(EARTH_BURNT_COLOR).unwrap()));
+        commands.spawn((
+            OnEndingScreen,
+            Flash,
+            GeometryBuilder::build_as(
+                &shapes::Circle {
+                    radius: 1.,
+                    center: Vec2::ZERO,
+                },
+                DrawMode::Fill(FillMode::color(Color::WHITE)),
+                Transform::from_translation(earth_transform.translation.truncate().extend(0.5)),
+            ),
+        ));
+    }
+
+    let t = ((elapsed - IMPACT_TIME) / FLASH_DURATION).clamp(0., 1.);
+    for (mut transform, mut mode) in flash_query.iter_mut() {
+        transform.scale = Vec3::splat(1. + t.sqrt() * FLASH_MAX_RADIUS);
+        *mode = DrawMode::Fill(FillMode::color(Color::rgba(1., 1., 1., 1. - t)));
+    }
+}
+
+fn ending_text(clock: Res<EndingClock>, mut text_query: Query<(&EndingText, &mut Visibility)>) {
+    for (EndingText(appears_at), mut visibility) in text_query.iter_mut() {
+        let is_visible = clock.0 >= *appears_at;
+        if visibility.is_visible != is_visible {
+            visibility.is_visible = is_visible;
+        }
+    }
+}
+
+fn game_over(
+    clock: Res<EndingClock>,
+    mut action_state: ResMut<ActionState>,
+    mut game_state: ResMut<State<GameState>>,
+) {
+    if clock.0 >= PROMPT_TIME && action_state.just_pressed(Action::Confirm) {
+        // The game over screen would otherwise take the same press as a restart.
+        action_state.clear_just_pressed(Action::Confirm);
+        // Pushed on top of the ending, like it is on top of the game, for the score and initials.
+        game_state.push(GameState::GameOver).unwrap();
+    }
+}
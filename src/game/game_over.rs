@@ -0,0 +1,291 @@
+use super::comet::Expedition;
+use super::health::{Health, HealthSystem, PlayerDamaged};
+use super::player::Player;
+use super::replay::AttractMode;
+use super::score::Score;
+use super::simulation::SimulationApp;
+use crate::high_score::{record_high_score, today, HighScoreEntry, HighScores, INITIALS_LENGTH};
+use crate::input::{Action, ActionState};
+use crate::{despawn_screen, GameState, Transition, FONT};
+
+use bevy::prelude::*;
+
+use bevy_rapier2d::prelude::*;
+
+/// What happened during the current run, shown on the game over screen.
+#[derive(Resource, Default)]
+pub struct RunStats {
+    pub hits_taken: u32,
+}
+
+#[derive(SystemLabel, Debug, Clone, PartialEq, Eq, Hash)]
+pub enum GameOverSystem {
+    Detect,
+}
+
+pub struct GameOverPlugin;
+
+impl Plugin for GameOverPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<RunStats>()
+            .add_system_set(SystemSet::on_enter(GameState::Game).with_system(reset_stats))
+            .add_simulation_system_set(
+                SystemSet::new()
+                    .with_system(update_stats.after(HealthSystem::Damage))
+                    .with_system(
+                        detect_game_over
+                            .label(GameOverSystem::Detect)
+                            .after(HealthSystem::Damage),
+                    ),
+            )
+            .add_system_set(
+                SystemSet::on_enter(GameState::GameOver)
+                    .with_system(freeze_physics)
+                    .with_system(transition)
+                    .with_system(setup_game_over),
+            )
+            .add_system_set(
+                SystemSet::on_update(GameState::GameOver)
+                    .with_system(enter_initials)
+                    .with_system(restart.after(enter_initials)),
+            )
+            .add_system_set(
+                SystemSet::on_exit(GameState::GameOver)
+                    .with_system(resume_physics)
+                    .with_system(despawn_screen::<OnGameOverScreen>),
+            );
+    }
+}
+
+// Tag component used to tag entities added on the game over screen
+#[derive(Component)]
+struct OnGameOverScreen;
+
+#[derive(Component)]
+struct InitialsText;
+
+#[derive(Component)]
+struct PromptText;
+
+// Kept for a confirmed entry with nothing typed, as a gamepad has no way to type.
+const ANONYMOUS_INITIALS: &str = "???";
+
+// Worded after the action rather than a key, as a gamepad confirms too.
+const INITIALS_PROMPT: &str = "Type your initials (keyboard only), Confirm to save";
+const RESTART_PROMPT: &str = "Confirm to restart";
+
+/// Present while the player types their initials for a new high score.
+#[derive(Resource, Default)]
+struct InitialsEntry(String);
+
+impl InitialsEntry {
+    fn display(&self) -> String {
+        format!("{:_<width$}", self.0, width = INITIALS_LENGTH)
+    }
+}
+
+pub fn freeze_physics(mut rapier_configuration: ResMut<RapierConfiguration>) {
+    rapier_configuration.physics_pipeline_active = false;
+}
+
+pub fn resume_physics(mut rapier_configuration: ResMut<RapierConfiguration>) {
+    rapier_configuration.physics_pipeline_active = true;
+}
+
+fn reset_stats(mut stats: ResMut<RunStats>) {
+    *stats = RunStats::default();
+}
+
+fn update_stats(mut stats: ResMut<RunStats>, mut damaged_events: EventReader<PlayerDamaged>) {
+    stats.hits_taken += damaged_events.iter().count() as u32;
+}
+
+fn detect_game_over(
+    mut game_state: ResMut<State<GameState>>,
+    expedition: Res<Expedition>,
+    attract_mode: Option<Res<AttractMode>>,
+    player_query: Query<&Health, With<Player>>,
+) {
+    // The run is won, the fade to the ending must not be cut short. A run shown on the splash
+    // screen goes back to it instead.
+    if expedition.is_over() || attract_mode.is_some() {
+        return;
+    }
+    if let Ok(health) = player_query.get_single() {
+        if health.is_dead() {
+            // Pushed on top of Game so the frozen field stays on screen behind the stats.
+            game_state.push(GameState::GameOver).unwrap();
+        }
+    }
+}
+
+fn transition(mut transition: ResMut<Transition>) {
+    transition.to_state = None;
+    transition.value = 0.;
+    transition.step = 0.01;
+    transition.is_playing = true;
+}
+
+fn setup_game_over(
+    mut commands: Commands,
+    stats: Res<RunStats>,
+    score: Res<Score>,
+    high_scores: Res<HighScores>,
+    asset_server: Res<AssetServer>,
+) {
+    let initials_entry = high_scores
+        .qualifies(score.total())
+        .then(InitialsEntry::default);
+
+    let font = asset_server.load(FONT);
+    let text_style = |font_size: f32| TextStyle {
+        font: font.clone(),
+        font_size,
+        color: Color::WHITE,
+    };
+
+    commands
+        .spawn((
+            OnGameOverScreen,
+            NodeBundle {
+                style: Style {
+                    size: Size::new(Val::Percent(100.), Val::Percent(100.)),
+                    flex_direction: FlexDirection::Column,
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    ..default()
+                },
+                ..default()
+            },
+        ))
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section("GAME OVER", text_style(80.)));
+            parent.spawn(TextBundle::from_section(
+                format!("SCORE {}", score.total()),
+                text_style(40.),
+            ));
+            let breakdown = [
+                format!(
+                    "Area       {:>6.0}      {:>6}",
+                    score.area_destroyed,
+                    score.area_points()
+                ),
+                format!(
+                    "Vaporised  {:>6}      {:>6}",
+                    score.fragments_vaporised,
+                    score.fragment_points()
+                ),
+                format!(
+                    "Monsters   {:>6}      {:>6}",
+                    score.monsters_killed,
+                    score.monster_points()
+                ),
+                format!(
+                    "Survived   {:>6.1} s    {:>6}",
+                    score.time_survived,
+                    score.time_points()
+                ),
+                format!("Hits taken {:>6}", stats.hits_taken),
+            ];
+            for line in breakdown {
+                parent.spawn(TextBundle::from_section(line, text_style(24.)));
+            }
+            if let Some(initials_entry) = &initials_entry {
+                parent.spawn((
+                    InitialsText,
+                    TextBundle::from_section(
+                        format!("NEW HIGH SCORE  {}", initials_entry.display()),
+                        text_style(30.),
+                    )
+                    .with_style(Style {
+                        margin: UiRect::top(Val::Px(30.)),
+                        ..default()
+                    }),
+                ));
+            }
+            let prompt = if initials_entry.is_some() {
+                INITIALS_PROMPT
+            } else {
+                RESTART_PROMPT
+            };
+            parent.spawn((
+                PromptText,
+                TextBundle::from_section(prompt, text_style(20.)).with_style(Style {
+                    margin: UiRect::top(Val::Px(40.)),
+                    ..default()
+                }),
+            ));
+        });
+
+    if let Some(initials_entry) = initials_entry {
+        commands.insert_resource(initials_entry);
+    }
+}
+
+fn enter_initials(
+    mut commands: Commands,
+    mut characters: EventReader<ReceivedCharacter>,
+    input: Res<Input<KeyCode>>,
+    mut action_state: ResMut<ActionState>,
+    initials_entry: Option<ResMut<InitialsEntry>>,
+    score: Res<Score>,
+    mut high_scores: ResMut<HighScores>,
+    mut initials_text_query: Query<&mut Text, (With<InitialsText>, Without<PromptText>)>,
+    mut prompt_text_query: Query<&mut Text, (With<PromptText>, Without<InitialsText>)>,
+) {
+    let Some(mut initials_entry) = initials_entry else {
+        characters.clear();
+        return;
+    };
+
+    for character in characters.iter() {
+        if character.char.is_ascii_alphanumeric() && initials_entry.0.len() < INITIALS_LENGTH {
+            initials_entry.0.push(character.char.to_ascii_uppercase());
+        }
+    }
+    if input.just_pressed(KeyCode::Back) {
+        initials_entry.0.pop();
+    }
+
+    let mut initials_text = initials_text_query.single_mut();
+    if action_state.just_pressed(Action::Confirm) {
+        // Otherwise the same press would also restart the game.
+        action_state.clear_just_pressed(Action::Confirm);
+        if initials_entry.0.is_empty() {
+            initials_entry.0 = ANONYMOUS_INITIALS.to_string();
+        }
+
+        let rank = record_high_score(
+            &mut high_scores,
+            HighScoreEntry {
+                initials: initials_entry.0.clone(),
+                score: score.total(),
+                date: today(),
+                duration: score.time_survived,
+            },
+        );
+        commands.remove_resource::<InitialsEntry>();
+
+        if let Some(rank) = rank {
+            initials_text.sections[0].value = format!("{} RANKED #{}", initials_entry.0, rank + 1);
+        }
+        prompt_text_query.single_mut().sections[0].value = RESTART_PROMPT.to_string();
+    } else if initials_entry.is_changed() {
+        initials_text.sections[0].value = format!("NEW HIGH SCORE  {}", initials_entry.display());
+    }
+}
+
+fn restart(
+    action_state: Res<ActionState>,
+    transition: Res<Transition>,
+    initials_entry: Option<Res<InitialsEntry>>,
+    mut game_state: ResMut<State<GameState>>,
+) {
+    if !transition.is_playing
+        && initials_entry.is_none()
+        && action_state.just_pressed(Action::Confirm)
+    {
+        // Unwinds GameOver and re-enters Game, so the game screen is despawned and spawned again.
+        game_state.replace(GameState::Game).unwrap();
+    }
+}
@@ -0,0 +1,150 @@
+use super::asteroid::Asteroid;
+use super::comet::Comet;
+use super::monster::Monster;
+use super::player::{Player, TailSegment};
+use super::simulation::{SimulationApp, SimulationClock};
+
+use bevy::prelude::*;
+
+use bevy_rapier2d::prelude::*;
+
+pub const PLAYER_MAX_HEALTH: f32 = 100.;
+
+// Contacts below this impulse are grazes and don't hurt.
+const MIN_DAMAGING_IMPULSE: f32 = 0.05;
+const DAMAGE_PER_IMPULSE: f32 = 40.;
+const MAX_HIT_DAMAGE: f32 = 35.;
+const INVULNERABILITY_DURATION: f32 = 1.;
+const INVULNERABILITY_BLINK_RATE: f32 = 12.;
+
+/// Contact force above which Rapier reports contacts on the player's colliders. Rapier reports
+/// forces, the impulse over a step, so it depends on how long steps are.
+pub fn player_contact_force_threshold(timestep: f32) -> f32 {
+    MIN_DAMAGING_IMPULSE / timestep
+}
+
+#[derive(Component, Debug)]
+pub struct Health {
+    pub current: f32,
+    pub max: f32,
+}
+
+impl Health {
+    pub fn new(max: f32) -> Self {
+        Health { current: max, max }
+    }
+
+    pub fn fraction(&self) -> f32 {
+        self.current / self.max
+    }
+
+    pub fn is_dead(&self) -> bool {
+        self.current <= 0.
+    }
+}
+
+/// Inserted after a hit; the entity can't be damaged until the timer finishes.
+#[derive(Component)]
+pub struct Invulnerability(Timer);
+
+impl Invulnerability {
+    fn new() -> Self {
+        Invulnerability(Timer::from_seconds(
+            INVULNERABILITY_DURATION,
+            TimerMode::Once,
+        ))
+    }
+}
+
+/// Sent every time the player loses health.
+pub struct PlayerDamaged {
+    pub amount: f32,
+    pub remaining: f32,
+    pub position: Vec2,
+}
+
+#[derive(SystemLabel, Debug, Clone, PartialEq, Eq, Hash)]
+pub enum HealthSystem {
+    Damage,
+    Invulnerability,
+}
+
+pub struct HealthPlugin;
+
+impl Plugin for HealthPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<PlayerDamaged>().add_simulation_system_set(
+            SystemSet::new()
+                .with_system(player_damage.label(HealthSystem::Damage))
+                .with_system(
+                    invulnerability
+                        .label(HealthSystem::Invulnerability)
+                        .after(HealthSystem::Damage),
+                ),
+        );
+    }
+}
+
+fn player_damage(
+    clock: Res<SimulationClock>,
+    mut commands: Commands,
+    mut contact_force_events: EventReader<ContactForceEvent>,
+    mut damaged_events: EventWriter<PlayerDamaged>,
+    mut player_query: Query<
+        (Entity, &mut Health, &Transform, Option<&Invulnerability>),
+        With<Player>,
+    >,
+    player_body_query: Query<(), Or<(With<Player>, With<TailSegment>)>>,
+    hazard_query: Query<(), Or<(With<Asteroid>, With<Monster>, With<Comet>)>>,
+) {
+    let Ok((player_entity, mut health, transform, invulnerability)) = player_query.get_single_mut()
+    else {
+        return;
+    };
+
+    // Only the strongest hit of the step counts, the others are absorbed by the invulnerability.
+    let strongest_impulse = contact_force_events
+        .iter()
+        .filter(|event| {
+            let (a, b) = (event.collider1, event.collider2);
+            (player_body_query.contains(a) && hazard_query.contains(b))
+                || (player_body_query.contains(b) && hazard_query.contains(a))
+        })
+        .map(|event| event.total_force_magnitude * clock.delta_seconds())
+        .fold(0., f32::max);
+
+    if invulnerability.is_some() || health.is_dead() || strongest_impulse < MIN_DAMAGING_IMPULSE {
+        return;
+    }
+
+    let amount = ((strongest_impulse - MIN_DAMAGING_IMPULSE) * DAMAGE_PER_IMPULSE)
+        .min(MAX_HIT_DAMAGE)
+        .min(health.current);
+    health.current -= amount;
+
+    commands
+        .entity(player_entity)
+        .insert(Invulnerability::new());
+    damaged_events.send(PlayerDamaged {
+        amount,
+        remaining: health.current,
+        position: transform.translation.truncate(),
+    });
+}
+
+fn invulnerability(
+    clock: Res<SimulationClock>,
+    mut commands: Commands,
+    mut query: Query<(Entity, &mut Invulnerability, &mut Visibility)>,
+) {
+    for (entity, mut invulnerability, mut visibility) in query.iter_mut() {
+        invulnerability.0.tick(clock.delta());
+        if invulnerability.0.finished() {
+            visibility.is_visible = true;
+            commands.entity(entity).remove::<Invulnerability>();
+        } else {
+            let blink = (invulnerability.0.elapsed_secs() * INVULNERABILITY_BLINK_RATE) as u32;
+            visibility.is_visible = blink.is_multiple_of(2);
+        }
+    }
+}
@@ -0,0 +1,283 @@
+use super::comet::Expedition;
+use super::health::Health;
+use super::laser::LaserEnergy;
+use super::player::Player;
+use super::score::Score;
+use super::wave::WaveDirector;
+use super::OnGameScreen;
+use crate::{GameState, MainCamera, FONT};
+
+use bevy::prelude::*;
+
+const BAR_WIDTH: f32 = 200.;
+const BAR_HEIGHT: f32 = 14.;
+
+/// Which camera draws the HUD.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum HudLayer {
+    /// Drawn by the post-processing camera, on top of the pixelated scene, so it stays crisp.
+    #[default]
+    PostProcessing,
+    /// Drawn by `MainCamera` into its render target, so it goes through the pixelation and
+    /// chromatic aberration with the rest of the scene.
+    Scene,
+}
+
+/// Insert before `GamePlugin` to override the defaults.
+#[derive(Resource, Default)]
+pub struct HudConfig {
+    pub layer: HudLayer,
+}
+
+#[derive(Component)]
+struct HealthBar;
+
+#[derive(Component)]
+struct LaserEnergyBar;
+
+#[derive(Component)]
+struct CometBar;
+
+#[derive(Component)]
+struct ScoreText;
+
+#[derive(Component)]
+struct WaveText;
+
+#[derive(Component)]
+struct EarthText;
+
+#[derive(SystemLabel, Debug, Clone, PartialEq, Eq, Hash)]
+pub enum HudSystem {
+    Update,
+}
+
+pub struct HudPlugin;
+
+impl Plugin for HudPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<HudConfig>()
+            .add_system(route_ui)
+            .add_system_set(SystemSet::on_enter(GameState::Game).with_system(hud_spawn))
+            // Shown once the simulation steps of the frame are done.
+            .add_system_set(
+                SystemSet::on_update(GameState::Game)
+                    .with_system(health_bar.label(HudSystem::Update))
+                    .with_system(laser_energy_bar.label(HudSystem::Update))
+                    .with_system(comet_bar.label(HudSystem::Update))
+                    .with_system(earth_text.label(HudSystem::Update))
+                    .with_system(score_text.label(HudSystem::Update))
+                    .with_system(wave_text.label(HudSystem::Update)),
+            );
+    }
+}
+
+/// Bevy UI is drawn by every camera that shows it, so only one of the two cameras must.
+/// Menus always stay crisp; the HUD follows `HudConfig` while playing.
+fn route_ui(
+    game_state: Res<State<GameState>>,
+    config: Res<HudConfig>,
+    mut cameras_query: Query<(&mut UiCameraConfig, Option<&MainCamera>)>,
+) {
+    if !game_state.is_changed() && !config.is_changed() {
+        return;
+    }
+
+    let ui_in_scene = *game_state.current() == GameState::Game && config.layer == HudLayer::Scene;
+    for (mut ui_camera_config, main_camera) in cameras_query.iter_mut() {
+        ui_camera_config.show_ui = main_camera.is_some() == ui_in_scene;
+    }
+}
+
+fn bar(
+    parent: &mut ChildBuilder,
+    marker: impl Component,
+    label: &str,
+    color: Color,
+    font: Handle<Font>,
+) {
+    parent
+        .spawn(NodeBundle {
+            style: Style {
+                align_items: AlignItems::Center,
+                margin: UiRect::bottom(Val::Px(6.)),
+                ..default()
+            },
+            ..default()
+        })
+        .with_children(|parent| {
+            parent.spawn(
+                TextBundle::from_section(
+                    label,
+                    TextStyle {
+                        font,
+                        font_size: 16.,
+                        color: Color::WHITE,
+                    },
+                )
+                .with_style(Style {
+                    size: Size::new(Val::Px(60.), Val::Auto),
+                    ..default()
+                }),
+            );
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        size: Size::new(Val::Px(BAR_WIDTH), Val::Px(BAR_HEIGHT)),
+                        ..default()
+                    },
+                    background_color: Color::hex("222222").unwrap().into(),
+                    ..default()
+                })
+                .with_children(|parent| {
+                    parent.spawn((
+                        marker,
+                        NodeBundle {
+                            style: Style {
+                                size: Size::new(Val::Percent(100.), Val::Percent(100.)),
+                                ..default()
+                            },
+                            background_color: color.into(),
+                            ..default()
+                        },
+                    ));
+                });
+        });
+}
+
+fn hud_spawn(mut commands: Commands, asset_server: Res<AssetServer>) {
+    let font = asset_server.load(FONT);
+    let text_style = TextStyle {
+        font: font.clone(),
+        font_size: 24.,
+        color: Color::WHITE,
+    };
+
+    commands
+        .spawn((
+            OnGameScreen,
+            NodeBundle {
+                style: Style {
+                    size: Size::new(Val::Percent(100.), Val::Percent(100.)),
+                    justify_content: JustifyContent::SpaceBetween,
+                    align_items: AlignItems::FlexStart,
+                    padding: UiRect::all(Val::Px(16.)),
+                    ..default()
+                },
+                ..default()
+            },
+        ))
+        .with_children(|parent| {
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        flex_direction: FlexDirection::Column,
+                        ..default()
+                    },
+                    ..default()
+                })
+                .with_children(|parent| {
+                    bar(
+                        parent,
+                        HealthBar,
+                        "HP",
+                        Color::hex("26b24a").unwrap(),
+                        font.clone(),
+                    );
+                    bar(
+                        parent,
+                        LaserEnergyBar,
+                        "LASER",
+                        Color::hex("ed1c24").unwrap(),
+                        font.clone(),
+                    );
+                    bar(
+                        parent,
+                        CometBar,
+                        "COMET",
+                        Color::hex("9ad9ff").unwrap(),
+                        font,
+                    );
+                });
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        flex_direction: FlexDirection::Column,
+                        align_items: AlignItems::FlexEnd,
+                        ..default()
+                    },
+                    ..default()
+                })
+                .with_children(|parent| {
+                    parent.spawn((ScoreText, TextBundle::from_section("0", text_style.clone())));
+                    parent.spawn((
+                        WaveText,
+                        TextBundle::from_section("WAVE 1", text_style.clone()),
+                    ));
+                    parent.spawn((EarthText, TextBundle::from_section("", text_style)));
+                });
+        });
+}
+
+fn health_bar(
+    player_query: Query<&Health, (With<Player>, Changed<Health>)>,
+    mut bar_query: Query<&mut Style, With<HealthBar>>,
+) {
+    if let Ok(health) = player_query.get_single() {
+        for mut style in bar_query.iter_mut() {
+            style.size.width = Val::Percent(health.fraction().max(0.) * 100.);
+        }
+    }
+}
+
+fn laser_energy_bar(
+    player_query: Query<&LaserEnergy, (With<Player>, Changed<LaserEnergy>)>,
+    mut bar_query: Query<(&mut Style, &mut BackgroundColor), With<LaserEnergyBar>>,
+) {
+    if let Ok(energy) = player_query.get_single() {
+        // Greyed out while the eyes are locked by an overheat.
+        let color = if energy.is_overheated() {
+            "777777"
+        } else {
+            "ed1c24"
+        };
+        for (mut style, mut background_color) in bar_query.iter_mut() {
+            style.size.width = Val::Percent(energy.level() * 100.);
+            *background_color = Color::hex(color).unwrap().into();
+        }
+    }
+}
+
+fn comet_bar(expedition: Res<Expedition>, mut bar_query: Query<&mut Style, With<CometBar>>) {
+    if expedition.is_changed() {
+        for mut style in bar_query.iter_mut() {
+            style.size.width = Val::Percent(expedition.progress() * 100.);
+        }
+    }
+}
+
+fn earth_text(expedition: Res<Expedition>, mut text_query: Query<&mut Text, With<EarthText>>) {
+    let value = format!("EARTH {:.0} KM", expedition.distance_to_earth());
+    for mut text in text_query.iter_mut() {
+        if text.sections[0].value != value {
+            text.sections[0].value = value.clone();
+        }
+    }
+}
+
+fn score_text(score: Res<Score>, mut text_query: Query<&mut Text, With<ScoreText>>) {
+    if score.is_changed() {
+        for mut text in text_query.iter_mut() {
+            text.sections[0].value = score.total().to_string();
+        }
+    }
+}
+
+fn wave_text(director: Res<WaveDirector>, mut text_query: Query<&mut Text, With<WaveText>>) {
+    let value = format!("WAVE {}", director.number());
+    for mut text in text_query.iter_mut() {
+        if text.sections[0].value != value {
+            text.sections[0].value = value.clone();
+        }
+    }
+}
@@ -0,0 +1,539 @@
+use super::asteroid::{
+    fragment_velocity, shatter, Asteroid, AsteroidConfig, AsteroidDestroyed, AsteroidShape,
+    AsteroidVaporised, Destructible, Integrity, ASTEROID_COLOR,
+};
+use super::camera::CameraSystem;
+use super::comet::Comet;
+use super::health::Health;
+use super::monster::{Monster, MonsterKilled};
+use super::particle::Particle;
+use super::player::{Controls, Direction, IsShooting, Player, PlayerSystem};
+use super::simulation::{GameRng, SimulationApp, SimulationClock};
+use super::wrap::{wrap_ray, WrapConfig};
+use super::{lerp_color, OnGameScreen};
+
+use bevy::prelude::*;
+
+use bevy_prototype_lyon::{
+    prelude::{FillMode, *},
+    shapes::Polygon,
+};
+
+use bevy_rapier2d::prelude::*;
+
+use std::collections::HashSet;
+use std::f32::consts::FRAC_PI_3;
+
+const LASER_DAMAGE_PER_SECOND: f32 = 60.;
+const LASER_MAX_LENGTH: f32 = 40.;
+const LASER_EXTENSION_SPEED: f32 = 600.;
+// Fragments smaller than this are vaporised instead of being left in the field.
+const MIN_DESTRUCTIBLE_AREA: f32 = 8.;
+// Seconds a vaporised fragment takes to fade out.
+const VAPORISED_FRAGMENT_LIFETIME: f32 = 0.5;
+
+const LASER_MAX_POWER: u32 = 5;
+const ASTEROIDS_PER_POWER_LEVEL: u32 = 10;
+
+const LASER_DRAIN_PER_SECOND: f32 = 0.35;
+const LASER_RECHARGE_PER_SECOND: f32 = 0.25;
+// Once overheated the eyes stay locked until the energy is back to this level.
+const LASER_OVERHEAT_RECOVERY_LEVEL: f32 = 0.6;
+
+const LASER_COLOR: &str = "ed1c24";
+const LASER_HOT_COLOR: &str = "fff200";
+const LASER_MIN_WIDTH: f32 = 0.1;
+const LASER_HOT_WIDTH: f32 = 0.35;
+
+/// Energy of the laser eyes, drained while shooting and recharged otherwise.
+#[derive(Component, Debug)]
+pub struct LaserEnergy {
+    level: f32,
+    overheated: bool,
+}
+
+impl Default for LaserEnergy {
+    fn default() -> Self {
+        LaserEnergy {
+            level: 1.,
+            overheated: false,
+        }
+    }
+}
+
+impl LaserEnergy {
+    pub fn level(&self) -> f32 {
+        self.level
+    }
+
+    pub fn heat(&self) -> f32 {
+        1. - self.level
+    }
+
+    pub fn is_overheated(&self) -> bool {
+        self.overheated
+    }
+
+    pub fn can_fire(&self) -> bool {
+        !self.overheated && self.level > 0.
+    }
+}
+
+/// Power of the beam, levelled up by destroying asteroids.
+#[derive(Component, Debug, Default)]
+pub struct LaserPower {
+    level: u32,
+    asteroids_destroyed: u32,
+}
+
+impl LaserPower {
+    pub fn level(&self) -> u32 {
+        self.level
+    }
+
+    fn max_length(&self) -> f32 {
+        LASER_MAX_LENGTH * (1. + 0.25 * self.level as f32)
+    }
+
+    fn extension_speed(&self) -> f32 {
+        LASER_EXTENSION_SPEED * (1. + 0.2 * self.level as f32)
+    }
+
+    fn damage_per_second(&self) -> f32 {
+        LASER_DAMAGE_PER_SECOND * (1. + 0.5 * self.level as f32)
+    }
+
+    fn extra_width(&self) -> f32 {
+        0.04 * self.level as f32
+    }
+
+    /// Fragments below this area can no longer be shattered, they get vaporised.
+    /// Powerful beams keep breaking down fragments the base laser would leave alone.
+    fn min_destructible_area(&self) -> f32 {
+        MIN_DESTRUCTIBLE_AREA / (1 << self.level.min(3)) as f32
+    }
+
+    fn add_destroyed_asteroid(&mut self) {
+        self.asteroids_destroyed += 1;
+        if self
+            .asteroids_destroyed
+            .is_multiple_of(ASTEROIDS_PER_POWER_LEVEL)
+        {
+            self.level = (self.level + 1).min(LASER_MAX_POWER);
+        }
+    }
+}
+
+#[derive(Component)]
+pub struct LaserRay {
+    height: f32,
+    position: Direction,
+}
+
+impl LaserRay {
+    fn new(position: Direction) -> Self {
+        LaserRay {
+            height: 0.1,
+            position,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum AimMode {
+    /// The eyes look where the head points.
+    #[default]
+    Head,
+    /// The eyes follow the cursor or the aim stick, twin-stick style.
+    Free,
+}
+
+/// Insert before `GamePlugin` to override the defaults.
+#[derive(Resource, Clone, Debug)]
+pub struct AimConfig {
+    pub mode: AimMode,
+    /// How far the eyes can turn away from the head, in radians.
+    pub max_angle: f32,
+}
+
+impl Default for AimConfig {
+    fn default() -> Self {
+        AimConfig {
+            mode: AimMode::Head,
+            max_angle: FRAC_PI_3,
+        }
+    }
+}
+
+/// The beam direction closest to `aim` within the cone the eyes can turn in.
+pub fn aim_direction(forward: Vec2, aim: Option<Vec2>, max_angle: f32) -> Vec2 {
+    let Some(aim) = aim.filter(|aim| *aim != Vec2::ZERO) else {
+        return forward;
+    };
+    let angle = forward.angle_between(aim);
+    if angle.is_nan() {
+        return forward;
+    }
+    Vec2::from_angle(angle.clamp(-max_angle, max_angle)).rotate(forward)
+}
+
+#[derive(SystemLabel, Debug, Clone, PartialEq, Eq, Hash)]
+pub enum LaserSystem {
+    Energy,
+    Eyes,
+    Rays,
+    Power,
+}
+
+pub struct LaserPlugin;
+
+impl Plugin for LaserPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<AimConfig>().add_simulation_system_set(
+            SystemSet::new()
+                .with_system(
+                    laser_energy
+                        .label(LaserSystem::Energy)
+                        .after(PlayerSystem::Controls),
+                )
+                .with_system(
+                    laser_eyes
+                        .label(LaserSystem::Eyes)
+                        .after(LaserSystem::Energy),
+                )
+                .with_system(
+                    lasers
+                        .label(LaserSystem::Rays)
+                        .after(LaserSystem::Eyes)
+                        .after(PlayerSystem::Movement)
+                        .before(CameraSystem::Follow),
+                )
+                .with_system(
+                    laser_power
+                        .label(LaserSystem::Power)
+                        .after(LaserSystem::Rays),
+                ),
+        );
+    }
+}
+
+fn laser_energy(
+    clock: Res<SimulationClock>,
+    mut query: Query<(&Controls, &mut LaserEnergy), With<Player>>,
+) {
+    let (controls, mut energy) = query.single_mut();
+    if controls.shooting && energy.can_fire() {
+        energy.level = (energy.level - LASER_DRAIN_PER_SECOND * clock.delta_seconds()).max(0.);
+        if energy.level == 0. {
+            energy.overheated = true;
+        }
+    } else {
+        energy.level = (energy.level + LASER_RECHARGE_PER_SECOND * clock.delta_seconds()).min(1.);
+        if energy.overheated && energy.level >= LASER_OVERHEAT_RECOVERY_LEVEL {
+            energy.overheated = false;
+        }
+    }
+}
+
+fn laser_power(
+    mut asteroid_destroyed_events: EventReader<AsteroidDestroyed>,
+    mut query: Query<&mut LaserPower, With<Player>>,
+) {
+    let mut power = query.single_mut();
+    for _ in asteroid_destroyed_events.iter() {
+        power.add_destroyed_asteroid();
+    }
+}
+
+fn is_firing(controls: &Controls, energy: &LaserEnergy) -> bool {
+    controls.shooting && energy.can_fire()
+}
+
+fn laser_eyes(
+    mut commands: Commands,
+    mut query: Query<(&Controls, &LaserEnergy, &mut IsShooting), With<Player>>,
+) {
+    let (controls, energy, mut is_shooting) = query.single_mut();
+    let firing = is_firing(controls, energy);
+    if let IsShooting(false) = *is_shooting {
+        if firing {
+            *is_shooting = IsShooting(true);
+
+            let color = Color::hex(LASER_COLOR).unwrap();
+            let line = shapes::Line(Vec2::ZERO, Vec2::ZERO);
+            let mut spawn_laser = |position: Direction| {
+                commands.spawn((
+                    LaserRay::new(position),
+                    OnGameScreen,
+                    GeometryBuilder::build_as(
+                        &line,
+                        DrawMode::Stroke(StrokeMode::new(color, LASER_MIN_WIDTH)),
+                        Transform::default(),
+                    ),
+                ));
+            };
+            spawn_laser(Direction::Left);
+            spawn_laser(Direction::Right);
+        }
+    } else {
+        *is_shooting = IsShooting(firing);
+    }
+}
+
+fn lasers(
+    clock: Res<SimulationClock>,
+    mut rng: ResMut<GameRng>,
+    rapier_context: Res<RapierContext>,
+    asteroid_config: Res<AsteroidConfig>,
+    wrap_config: Res<WrapConfig>,
+    aim_config: Res<AimConfig>,
+    mut commands: Commands,
+    mut ray_query: Query<
+        (
+            Entity,
+            &mut Transform,
+            &mut Path,
+            &mut DrawMode,
+            &mut LaserRay,
+        ),
+        With<LaserRay>,
+    >,
+    player_query: Query<
+        (Entity, &Controls, &LaserEnergy, &LaserPower, &Transform),
+        (With<Player>, Without<LaserRay>),
+    >,
+    mut asteroid_query: Query<
+        (
+            &AsteroidShape,
+            &ColliderMassProperties,
+            &Transform,
+            &Velocity,
+            &Destructible,
+            Option<&mut Integrity>,
+        ),
+        (With<Asteroid>, Without<LaserRay>),
+    >,
+    mut monster_query: Query<(&mut Health, &Transform), (With<Monster>, Without<LaserRay>)>,
+    mut hull_query: Query<&mut Health, (With<Comet>, Without<Monster>, Without<LaserRay>)>,
+    mut asteroid_destroyed_events: EventWriter<AsteroidDestroyed>,
+    mut asteroid_vaporised_events: EventWriter<AsteroidVaporised>,
+    mut monster_killed_events: EventWriter<MonsterKilled>,
+) {
+    let (player_entity, controls, energy, power, rb_transform) = player_query.single();
+    let firing = is_firing(controls, energy);
+    let heat = energy.heat();
+
+    // Both eyes can hit the same asteroid in a step, it must only shatter once.
+    let mut shattered = HashSet::new();
+
+    let rotation_as_vector = rb_transform.rotation.mul_vec3(Vec3::new(0.0, 1.0, 0.0));
+    let forward = Vec2::new(rotation_as_vector.x, rotation_as_vector.y);
+    let direction = match aim_config.mode {
+        AimMode::Head => forward,
+        AimMode::Free => aim_direction(forward, controls.aim, aim_config.max_angle),
+    };
+    let center = rb_transform.translation;
+
+    for (entity, mut transform, mut path, mut mode, mut laser) in ray_query.iter_mut() {
+        if !firing {
+            commands.entity(entity).despawn();
+        } else {
+            laser.height += power.extension_speed() * clock.delta_seconds();
+            laser.height = laser.height.min(power.max_length());
+
+            let filter = QueryFilter::default().exclude_collider(player_entity);
+            let ray_pos = Vec2::new(transform.translation.x, transform.translation.y);
+            let mut pieces = match wrap_config.arena {
+                Some(arena) => wrap_ray(arena, ray_pos, direction, laser.height),
+                None => vec![(ray_pos, laser.height)],
+            };
+
+            // Each piece is cast in turn until one of them hits something.
+            let mut hit = None;
+            let mut travelled = 0.;
+            for (index, (start, length)) in pieces.iter_mut().enumerate() {
+                if let Some((entity, toi)) =
+                    rapier_context.cast_ray(*start, direction, *length, true, filter)
+                {
+                    *length = toi;
+                    travelled += toi;
+                    hit = Some((entity, *start + direction * toi, index));
+                    break;
+                }
+                travelled += *length;
+            }
+            laser.height = laser.height.min(travelled);
+            if let Some((_, _, index)) = hit {
+                pieces.truncate(index + 1);
+            }
+
+            if let Some((entity, hit_point, _)) = hit {
+                if let Ok((
+                    parent,
+                    asteroid_mass_properties,
+                    &asteroid_transform,
+                    &asteroid_velocity,
+                    Destructible(is_destructible),
+                    integrity,
+                )) = asteroid_query.get_mut(entity)
+                {
+                    let depleted = match integrity {
+                        Some(mut integrity) => {
+                            integrity.current -= power.damage_per_second() * clock.delta_seconds();
+                            integrity.is_depleted()
+                        }
+                        None => true,
+                    };
+                    if *is_destructible && depleted && shattered.insert(entity) {
+                        asteroid_destroyed_events.send(AsteroidDestroyed {
+                            area: parent.area,
+                            position: asteroid_transform.translation.truncate(),
+                        });
+
+                        let local_hit_point = asteroid_transform
+                            .compute_affine()
+                            .inverse()
+                            .transform_point3(hit_point.extend(0.))
+                            .truncate();
+                        let parent_center = asteroid_transform
+                            .transform_point(parent.centroid.extend(0.))
+                            .truncate();
+
+                        let fragments =
+                            shatter(parent, local_hit_point, asteroid_config.fracture, &mut *rng);
+                        for fragment_shape in fragments {
+                            let Some(collider) = fragment_shape.collider() else {
+                                continue;
+                            };
+
+                            let area = fragment_shape.area;
+                            let is_destructible = area >= power.min_destructible_area();
+                            let center = asteroid_transform
+                                .transform_point(fragment_shape.centroid.extend(0.))
+                                .truncate();
+
+                            if !is_destructible {
+                                asteroid_vaporised_events.send(AsteroidVaporised {
+                                    area,
+                                    position: center,
+                                });
+                            }
+
+                            let velocity =
+                                fragment_velocity(&asteroid_velocity, parent_center, center);
+
+                            let shape = Polygon {
+                                points: fragment_shape.vertices.clone(),
+                                closed: true,
+                            };
+
+                            let mut fragment = commands.spawn((
+                                Asteroid,
+                                OnGameScreen,
+                                RigidBody::Dynamic,
+                                collider,
+                                velocity,
+                                fragment_shape,
+                                Destructible(is_destructible),
+                                *asteroid_mass_properties,
+                                GeometryBuilder::build_as(
+                                    &shape,
+                                    DrawMode::Fill(FillMode::color(
+                                        Color::hex(ASTEROID_COLOR).unwrap(),
+                                    )),
+                                    asteroid_transform,
+                                ),
+                            ));
+
+                            if is_destructible {
+                                fragment.insert(Integrity::from_area(area));
+                            } else {
+                                fragment.insert(Particle::new(
+                                    Vec2::new(0., 0.),
+                                    VAPORISED_FRAGMENT_LIFETIME,
+                                ));
+                            }
+                        }
+
+                        commands.entity(entity).despawn();
+                    }
+                } else if let Ok((mut health, monster_transform)) = monster_query.get_mut(entity) {
+                    if !health.is_dead() {
+                        health.current -= power.damage_per_second() * clock.delta_seconds();
+                        if health.is_dead() {
+                            monster_killed_events.send(MonsterKilled {
+                                position: monster_transform.translation.truncate(),
+                            });
+                            commands.entity(entity).despawn();
+                        }
+                    }
+                } else if let Ok(mut hull) = hull_query.get_mut(entity) {
+                    // Breaking the comet up is left to the comet itself.
+                    hull.current -= power.damage_per_second() * clock.delta_seconds();
+                }
+            }
+
+            let offset = match laser.position {
+                Direction::Left => -0.22,
+                Direction::Right => 0.28,
+            };
+
+            let mut builder = PathBuilder::new();
+            for (start, length) in pieces {
+                let start = start - ray_pos;
+                builder.move_to(start);
+                builder.line_to(start + direction * length);
+            }
+            *path = builder.build();
+            transform.translation = Vec3::new(
+                center.x + rotation_as_vector.x.cos() * offset + forward.x * 5.,
+                center.y + rotation_as_vector.y.sin() * offset + forward.y * 5.,
+                0.2,
+            );
+
+            // The beam gets thicker, hotter and more unstable as the eyes heat up.
+            let color = lerp_color(
+                Color::hex(LASER_COLOR).unwrap(),
+                Color::hex(LASER_HOT_COLOR).unwrap(),
+                heat,
+            );
+            let min_width = LASER_MIN_WIDTH + power.extra_width();
+            let width = min_width + (LASER_HOT_WIDTH - LASER_MIN_WIDTH) * heat;
+            let flicker = (clock.elapsed_seconds() * 60.).sin() * 0.05 * (1. + heat);
+            let width = (width + flicker).max(min_width);
+            *mode = DrawMode::Stroke(StrokeMode::new(color, width));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use proptest::prelude::*;
+
+    proptest! {
+        #[test]
+        fn eyes_stay_within_the_cone(
+            heading in 0f32..std::f32::consts::TAU,
+            target in 0f32..std::f32::consts::TAU,
+            max_angle in 0f32..std::f32::consts::PI,
+        ) {
+            let forward = Vec2::from_angle(heading);
+            let direction = aim_direction(forward, Some(Vec2::from_angle(target) * 3.), max_angle);
+
+            prop_assert!((direction.length() - 1.).abs() < 1e-4);
+            prop_assert!(forward.angle_between(direction).abs() <= max_angle + 1e-4);
+        }
+    }
+
+    #[test]
+    fn eyes_look_straight_at_targets_inside_the_cone() {
+        let direction = aim_direction(Vec2::Y, Some(Vec2::new(1., 2.)), FRAC_PI_3);
+        assert!((direction - Vec2::new(1., 2.).normalize()).length() < 1e-5);
+
+        let clamped = aim_direction(Vec2::Y, Some(Vec2::NEG_Y + Vec2::X * 0.1), FRAC_PI_3);
+        assert!((clamped - Vec2::from_angle(-FRAC_PI_3).rotate(Vec2::Y)).length() < 1e-5);
+        assert_eq!(aim_direction(Vec2::Y, None, FRAC_PI_3), Vec2::Y);
+    }
+}
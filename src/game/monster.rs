@@ -0,0 +1,62 @@
+use super::health::Health;
+use super::steering::{
+    AsteroidAvoidanceBehavior, KeepVerticalBehavior, SeekBehavior, SeparationBehavior, Steering,
+    Target,
+};
+use super::{OnGameScreen, PIXEL_TO_METERS};
+
+use bevy::prelude::*;
+
+use bevy_rapier2d::prelude::*;
+
+#[derive(Component)]
+pub struct Monster;
+
+/// Sent when a monster's health runs out.
+pub struct MonsterKilled {
+    pub position: Vec2,
+}
+
+const MONSTER_MAX_HEALTH: f32 = 100.;
+
+pub struct MonsterPlugin;
+
+impl Plugin for MonsterPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<MonsterKilled>();
+    }
+}
+
+pub fn spawn_monster(commands: &mut Commands, asset_server: &AssetServer, translation: Vec2) {
+    let half_body_width = 198. * PIXEL_TO_METERS;
+    let half_body_height = 184. * PIXEL_TO_METERS;
+
+    let collider = Collider::cuboid(half_body_width, half_body_height);
+    commands.spawn((
+        Monster,
+        OnGameScreen,
+        RigidBody::Dynamic,
+        collider,
+        Health::new(MONSTER_MAX_HEALTH),
+        Velocity::default(),
+        ExternalForce::default(),
+        Damping {
+            linear_damping: 0.5,
+            angular_damping: 1.0,
+        },
+        Steering::new(10., 10., 0.8)
+            .with(1., SeekBehavior::new(Target::Player))
+            .with(2., AsteroidAvoidanceBehavior::new(1.))
+            .with(1., SeparationBehavior::new(10.))
+            .with(1., KeepVerticalBehavior::new(0.8)),
+        SpriteBundle {
+            texture: asset_server.load("monster.png"),
+            sprite: Sprite {
+                custom_size: Some(Vec2::new(half_body_width * 2., half_body_height * 2.)),
+                ..Default::default()
+            },
+            transform: Transform::from_translation(translation.extend(0.)),
+            ..Default::default()
+        },
+    ));
+}
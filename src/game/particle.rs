@@ -0,0 +1,65 @@
+use super::simulation::{SimulationApp, SimulationClock};
+
+use bevy::prelude::*;
+
+use bevy_prototype_lyon::prelude::{FillMode, *};
+
+pub const JETPACK_PARTICLE_COLORS: [&str; 3] = ["fff200", "ed1c24", "ff7f27"];
+/// In seconds.
+pub const JETPACK_PARTICLE_LIFETIME: f32 = 0.5;
+
+#[derive(Component)]
+pub struct Particle {
+    lifetime: f32,
+    remaining: f32,
+    direction: Vec2,
+}
+
+impl Particle {
+    pub fn new(direction: Vec2, lifetime: f32) -> Self {
+        Particle {
+            lifetime,
+            remaining: lifetime,
+            direction,
+        }
+    }
+}
+
+#[derive(SystemLabel, Debug, Clone, PartialEq, Eq, Hash)]
+pub enum ParticleSystem {
+    Update,
+}
+
+pub struct ParticlePlugin;
+
+impl Plugin for ParticlePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_simulation_system_set(
+            SystemSet::new().with_system(particles.label(ParticleSystem::Update)),
+        );
+    }
+}
+
+fn particles(
+    clock: Res<SimulationClock>,
+    mut commands: Commands,
+    mut query: Query<(Entity, &mut Transform, &mut Particle, &mut DrawMode)>,
+) {
+    let dt = clock.delta_seconds();
+    for (entity, mut transform, mut particle, mut mode) in query.iter_mut() {
+        particle.remaining -= dt;
+        if particle.remaining <= 0. {
+            commands.entity(entity).despawn();
+        } else {
+            let translation = &mut transform.translation;
+            translation.x += particle.direction.x * dt * 10.;
+            translation.y += particle.direction.y * dt * 10.;
+
+            if let DrawMode::Fill(fill_mode) = *mode {
+                let mut color = fill_mode.color;
+                color.set_a(particle.remaining / particle.lifetime);
+                *mode = DrawMode::Fill(FillMode::color(color));
+            }
+        }
+    }
+}
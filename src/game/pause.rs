@@ -0,0 +1,69 @@
+use super::game_over::{freeze_physics, resume_physics};
+use crate::input::{Action, ActionState};
+use crate::{despawn_screen, GameState, FONT};
+
+use bevy::prelude::*;
+
+pub struct PausePlugin;
+
+impl Plugin for PausePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system_set(SystemSet::on_update(GameState::Game).with_system(pause))
+            .add_system_set(
+                SystemSet::on_enter(GameState::Paused)
+                    .with_system(freeze_physics)
+                    .with_system(setup_pause),
+            )
+            .add_system_set(SystemSet::on_update(GameState::Paused).with_system(unpause))
+            .add_system_set(
+                SystemSet::on_exit(GameState::Paused)
+                    .with_system(resume_physics)
+                    .with_system(despawn_screen::<OnPauseScreen>),
+            );
+    }
+}
+
+// Tag component used to tag entities added on the pause screen
+#[derive(Component)]
+struct OnPauseScreen;
+
+fn pause(mut action_state: ResMut<ActionState>, mut game_state: ResMut<State<GameState>>) {
+    if action_state.just_pressed(Action::Pause) {
+        // Otherwise the Paused state would see the same press and unpause in the same frame.
+        action_state.clear_just_pressed(Action::Pause);
+        game_state.push(GameState::Paused).unwrap();
+    }
+}
+
+fn unpause(mut action_state: ResMut<ActionState>, mut game_state: ResMut<State<GameState>>) {
+    if action_state.just_pressed(Action::Pause) {
+        action_state.clear_just_pressed(Action::Pause);
+        game_state.pop().unwrap();
+    }
+}
+
+fn setup_pause(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands
+        .spawn((
+            OnPauseScreen,
+            NodeBundle {
+                style: Style {
+                    size: Size::new(Val::Percent(100.), Val::Percent(100.)),
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    ..default()
+                },
+                ..default()
+            },
+        ))
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section(
+                "PAUSED",
+                TextStyle {
+                    font: asset_server.load(FONT),
+                    font_size: 60.,
+                    color: Color::WHITE,
+                },
+            ));
+        });
+}
@@ -0,0 +1,254 @@
+use super::health::{player_contact_force_threshold, Health, PLAYER_MAX_HEALTH};
+use super::laser::{LaserEnergy, LaserPower};
+use super::particle::{Particle, JETPACK_PARTICLE_COLORS, JETPACK_PARTICLE_LIFETIME};
+use super::replay::Playback;
+use super::simulation::{GameRng, SimulationApp, SimulationClock};
+use super::{OnGameScreen, PIXEL_TO_METERS};
+use crate::input::{cursor_to_world, Action, ActionState};
+use crate::{GameState, MainCamera};
+
+use bevy::prelude::*;
+
+use bevy_prototype_lyon::prelude::{FillMode, *};
+
+use bevy_rapier2d::prelude::*;
+
+use rand::seq::SliceRandom;
+use rand::Rng;
+
+use serde::{Deserialize, Serialize};
+
+const MAX_ANGULAR_SPEED: f32 = 5.;
+
+#[derive(Component)]
+pub struct Player;
+
+// Tag component for the jointed segments of the dino's tail
+#[derive(Component)]
+pub struct TailSegment;
+
+#[derive(Debug)]
+pub enum Direction {
+    Left,
+    Right,
+}
+
+#[derive(Component)]
+pub struct IsShooting(pub bool);
+
+/// Everything the player feeds into the simulation in a step, which is what replays record.
+#[derive(Component, Serialize, Deserialize, Clone, Copy, Default, Debug, PartialEq)]
+pub struct Controls {
+    /// From -1 (clockwise) to 1 (counterclockwise), proportional to the stick.
+    pub rotation: f32,
+    pub propulsion: bool,
+    pub shooting: bool,
+    /// Where the eyes would like to look in the world, from the aim stick or else the cursor.
+    pub aim: Option<Vec2>,
+}
+
+#[derive(SystemLabel, Debug, Clone, PartialEq, Eq, Hash)]
+pub enum PlayerSystem {
+    Spawn,
+    Controls,
+    Movement,
+    Jetpack,
+}
+
+pub struct PlayerPlugin;
+
+impl Plugin for PlayerPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system_set(
+            SystemSet::on_enter(GameState::Game)
+                .with_system(player_spawn.label(PlayerSystem::Spawn)),
+        )
+        .add_simulation_system_set(
+            SystemSet::new()
+                .with_system(controls.label(PlayerSystem::Controls))
+                .with_system(
+                    player
+                        .label(PlayerSystem::Movement)
+                        .after(PlayerSystem::Controls),
+                )
+                .with_system(
+                    jetpack
+                        .label(PlayerSystem::Jetpack)
+                        .after(PlayerSystem::Controls),
+                ),
+        );
+    }
+}
+
+fn controls(
+    action_state: Res<ActionState>,
+    windows: Res<Windows>,
+    camera_query: Query<(&Camera, &GlobalTransform), With<MainCamera>>,
+    playback: Option<ResMut<Playback>>,
+    mut query: Query<(&mut Controls, &Transform), With<Player>>,
+) {
+    let (mut controls, transform) = query.single_mut();
+    // A replay stands in for the input devices until it runs out.
+    if let Some(recorded) = playback.and_then(|mut playback| playback.next_step()) {
+        *controls = recorded;
+        return;
+    }
+
+    controls.rotation =
+        action_state.value(Action::RotateLeft) - action_state.value(Action::RotateRight);
+    controls.propulsion = action_state.pressed(Action::Thrust);
+    controls.shooting = action_state.pressed(Action::Fire);
+
+    let cursor_aim = || {
+        let window = windows.get_primary()?;
+        let cursor = window.cursor_position()?;
+        let (camera, camera_transform) = camera_query.get_single().ok()?;
+        let ndc_to_world = camera_transform.compute_matrix() * camera.projection_matrix().inverse();
+        let window_size = Vec2::new(window.width(), window.height());
+        let target = cursor_to_world(cursor, window_size, ndc_to_world);
+        Some(target - transform.translation.truncate())
+    };
+    controls.aim = action_state.aim().or_else(cursor_aim);
+}
+
+fn player(
+    mut query: Query<(&Controls, &Transform, &mut Velocity, &mut ExternalImpulse), With<Player>>,
+) {
+    let (controls, transform, mut velocity, mut impulse) = query.single_mut();
+    velocity.angvel = controls.rotation * MAX_ANGULAR_SPEED;
+
+    let vector = transform.rotation.mul_vec3(Vec3::new(0.0, 1.0, 0.0));
+    let direction = Vec2::new(vector.x, vector.y);
+    if controls.propulsion {
+        impulse.impulse = direction * 1.5;
+    }
+}
+
+fn jetpack(
+    mut commands: Commands,
+    mut rng: ResMut<GameRng>,
+    query: Query<(&Controls, &Transform), With<Player>>,
+) {
+    let (controls, rb_transform) = query.single();
+    if controls.propulsion {
+        let rotation_as_vector = rb_transform.rotation.mul_vec3(Vec3::new(0.0, 1.0, 0.0));
+        let particle_direction = Vec2::new(rotation_as_vector.x, rotation_as_vector.y) * -1.;
+        let center = rb_transform.translation;
+
+        let mut spawn_particle = |offset: f32| {
+            let shape = shapes::Rectangle {
+                extents: Vec2::new(0.7, 0.7),
+                origin: shapes::RectangleOrigin::Center,
+            };
+            let color = Color::hex(JETPACK_PARTICLE_COLORS.choose(&mut *rng).unwrap()).unwrap();
+            commands.spawn((
+                Particle::new(particle_direction, JETPACK_PARTICLE_LIFETIME),
+                OnGameScreen,
+                GeometryBuilder::build_as(
+                    &shape,
+                    DrawMode::Fill(FillMode::color(color)),
+                    Transform::from_translation(Vec3::new(
+                        center.x
+                            + rotation_as_vector.x.cos() * (offset + rng.gen_range(-0.7..0.7))
+                            + particle_direction.x * 3.8,
+                        center.y
+                            + rotation_as_vector.y.cos() * (offset + rng.gen_range(-0.7..0.7))
+                            + particle_direction.y * 3.8,
+                        0.1,
+                    )),
+                ),
+            ));
+        };
+
+        spawn_particle(0.8);
+        spawn_particle(-0.8);
+    }
+}
+
+fn player_spawn(
+    mut commands: Commands,
+    clock: Res<SimulationClock>,
+    asset_server: Res<AssetServer>,
+) {
+    let contact_force_threshold = player_contact_force_threshold(clock.delta_seconds());
+    let half_body_width = 110. * PIXEL_TO_METERS;
+    let half_body_height = 280. * PIXEL_TO_METERS;
+
+    let collider = Collider::cuboid(half_body_width, half_body_height);
+
+    let transform = Transform::from_xyz(0.0, half_body_height, 0.);
+
+    let mut prev_id = commands
+        .spawn((
+            Player,
+            OnGameScreen,
+            RigidBody::Dynamic,
+            collider,
+            Velocity::default(),
+            ExternalImpulse::default(),
+            Damping {
+                linear_damping: 0.5,
+                angular_damping: 1.0,
+            },
+            SpriteBundle {
+                texture: asset_server.load("dino.png"),
+                sprite: Sprite {
+                    custom_size: Some(Vec2::new(half_body_width * 2., half_body_height * 2.)),
+                    ..Default::default()
+                },
+                transform,
+                ..Default::default()
+            },
+            IsShooting(false),
+            LaserEnergy::default(),
+            LaserPower::default(),
+            Controls::default(),
+            Health::new(PLAYER_MAX_HEALTH),
+            ActiveEvents::CONTACT_FORCE_EVENTS,
+            ContactForceEventThreshold(contact_force_threshold),
+        ))
+        .id();
+
+    let mut pos_y = -half_body_height - 0.7;
+    let mut prev_half_height = half_body_height;
+    let mut half_width = 0.5;
+
+    for _ in 0..8 {
+        let half_height = half_width * 1.2;
+
+        let collider = Collider::cuboid(half_width, half_height);
+
+        let shape = shapes::Rectangle {
+            extents: Vec2::new(half_width * 2., half_height * 2.),
+            origin: shapes::RectangleOrigin::Center,
+        };
+
+        let joint = RevoluteJointBuilder::new()
+            .local_anchor1(Vec2::new(0.0, -prev_half_height))
+            .local_anchor2(Vec2::new(0.0, half_height))
+            .limits([-10., 10.]);
+
+        let id = commands
+            .spawn((
+                TailSegment,
+                RigidBody::Dynamic,
+                OnGameScreen,
+                collider,
+                GeometryBuilder::build_as(
+                    &shape,
+                    DrawMode::Fill(FillMode::color(Color::hex("26b24a").unwrap())),
+                    Transform::from_xyz(0., pos_y, 0.),
+                ),
+                ImpulseJoint::new(prev_id, joint),
+                ColliderMassProperties::Density(100.0),
+                ActiveEvents::CONTACT_FORCE_EVENTS,
+                ContactForceEventThreshold(contact_force_threshold),
+            ))
+            .id();
+
+        pos_y -= half_height * 2.;
+        half_width -= 0.05;
+        prev_id = id;
+        prev_half_height = half_height;
+    }
+}
@@ -0,0 +1,407 @@
+use super::player::{Controls, Player, PlayerSystem};
+use super::simulation::{
+    arg_value, GameRng, SimulationApp, SimulationClock, SimulationConfig, SimulationSystem,
+};
+use crate::{GameState, Transition};
+
+use bevy::{input::InputSystem, prelude::*};
+
+use directories::ProjectDirs;
+
+use serde::{Deserialize, Serialize};
+
+use std::{
+    fs,
+    io::{self, Write},
+    path::{Path, PathBuf},
+};
+
+const LAST_REPLAY_FILE: &str = "last_replay.ron";
+
+/// The controls of every step of a run, with what it takes to simulate the run again.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct Replay {
+    pub seed: u64,
+    pub timestep: f32,
+    /// Run-length encoded: how many steps in a row the controls were held for.
+    steps: Vec<(u32, Controls)>,
+}
+
+impl Replay {
+    pub fn new(seed: u64, timestep: f32) -> Self {
+        Replay {
+            seed,
+            timestep,
+            steps: Vec::new(),
+        }
+    }
+
+    pub fn push(&mut self, controls: Controls) {
+        match self.steps.last_mut() {
+            Some((count, last)) if *last == controls => *count += 1,
+            _ => self.steps.push((1, controls)),
+        }
+    }
+
+    /// Steps recorded.
+    pub fn len(&self) -> u64 {
+        self.steps.iter().map(|(count, _)| *count as u64).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.steps.is_empty()
+    }
+
+    /// The simulation the run was recorded with.
+    pub fn simulation_config(&self) -> SimulationConfig {
+        SimulationConfig {
+            seed: Some(self.seed),
+            timestep: self.timestep,
+        }
+    }
+
+    pub fn load(path: &Path) -> io::Result<Self> {
+        let contents = fs::read_to_string(path)?;
+        ron::from_str(&contents).map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))
+    }
+
+    /// Written in one line, and like the high scores through a temporary file.
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let contents = ron::to_string(self)
+            .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))?;
+
+        let temporary_path = path.with_extension("ron.tmp");
+        let mut file = fs::File::create(&temporary_path)?;
+        file.write_all(contents.as_bytes())?;
+        file.sync_all()?;
+        fs::rename(&temporary_path, path)
+    }
+}
+
+pub fn last_replay_path() -> Option<PathBuf> {
+    ProjectDirs::from("", "", "ultradino2000").map(|dirs| dirs.data_dir().join(LAST_REPLAY_FILE))
+}
+
+/// Loads the replay given with `--replay <path>`, if any.
+pub fn replay_from_args(args: &[String]) -> Option<Replay> {
+    let path = Path::new(arg_value(args, "--replay")?);
+    Replay::load(path)
+        .map_err(|error| warn!("Could not load replay from {}: {}", path.display(), error))
+        .ok()
+}
+
+/// The run being played, as it goes.
+#[derive(Resource, Default, Debug)]
+pub struct Recording(pub Replay);
+
+/// Present when a replay is fed to the simulation instead of the input devices. Every run
+/// plays it again from the start.
+#[derive(Resource, Debug)]
+pub struct Playback {
+    replay: Replay,
+    run: usize,
+    step: u32,
+}
+
+impl Playback {
+    pub fn new(replay: Replay) -> Self {
+        Playback {
+            replay,
+            run: 0,
+            step: 0,
+        }
+    }
+
+    /// The controls of the next step, until the replay runs out.
+    pub fn next_step(&mut self) -> Option<Controls> {
+        let (count, controls) = *self.replay.steps.get(self.run)?;
+        self.step += 1;
+        if self.step >= count {
+            self.run += 1;
+            self.step = 0;
+        }
+        Some(controls)
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.run >= self.replay.steps.len()
+    }
+
+    fn rewind(&mut self) {
+        self.run = 0;
+        self.step = 0;
+    }
+}
+
+/// Present while the splash screen shows the last run by itself, until a key or button is
+/// pressed or the replay runs out.
+#[derive(Resource, Debug)]
+pub struct AttractMode {
+    /// What the player's own runs go back to afterwards.
+    seed: Option<u64>,
+}
+
+/// The last run, if there is one that the simulation can play with its timestep.
+pub fn attract_replay(timestep: f32) -> Option<Replay> {
+    let replay = Replay::load(&last_replay_path()?).ok()?;
+    (!replay.is_empty() && replay.timestep == timestep).then_some(replay)
+}
+
+/// Plays `replay` in the next run, which the splash screen starts like any other.
+pub fn start_attract_mode(commands: &mut Commands, config: &mut SimulationConfig, replay: Replay) {
+    commands.insert_resource(AttractMode { seed: config.seed });
+    config.seed = Some(replay.seed);
+    commands.insert_resource(Playback::new(replay));
+}
+
+pub struct ReplayPlugin;
+
+impl Plugin for ReplayPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Recording>()
+            .add_system_set(
+                SystemSet::on_enter(GameState::Game)
+                    .with_system(start_recording.after(SimulationSystem::Reset))
+                    .with_system(rewind_playback),
+            )
+            .add_simulation_system_set(
+                SystemSet::new().with_system(record.after(PlayerSystem::Controls)),
+            )
+            // Whenever the run stops, so there is something to attach to a bug report.
+            .add_system_set(SystemSet::on_pause(GameState::Game).with_system(save_recording))
+            .add_system_set(SystemSet::on_exit(GameState::Game).with_system(save_recording))
+            // Before the game's own systems see the press or the state, so that none of them
+            // tries to change the state as well.
+            .add_system_to_stage(CoreStage::PreUpdate, end_attract_mode.after(InputSystem))
+            .add_system_set(SystemSet::on_enter(GameState::Splash).with_system(stop_attract_mode));
+    }
+}
+
+fn start_recording(
+    rng: Res<GameRng>,
+    clock: Res<SimulationClock>,
+    mut recording: ResMut<Recording>,
+) {
+    recording.0 = Replay::new(rng.seed(), clock.delta_seconds());
+}
+
+fn rewind_playback(playback: Option<ResMut<Playback>>) {
+    if let Some(mut playback) = playback {
+        playback.rewind();
+    }
+}
+
+fn record(mut recording: ResMut<Recording>, query: Query<&Controls, With<Player>>) {
+    if let Ok(controls) = query.get_single() {
+        recording.0.push(*controls);
+    }
+}
+
+fn save_recording(recording: Res<Recording>, attract_mode: Option<Res<AttractMode>>) {
+    // A replay of the last run is not worth keeping over it.
+    if recording.0.is_empty() || attract_mode.is_some() {
+        return;
+    }
+    match last_replay_path() {
+        Some(path) => {
+            if let Err(error) = recording.0.save(&path) {
+                warn!("Could not save replay to {}: {}", path.display(), error);
+            }
+        }
+        None => warn!("No data directory available, the replay won't be saved"),
+    }
+}
+
+fn end_attract_mode(
+    attract_mode: Option<Res<AttractMode>>,
+    playback: Option<Res<Playback>>,
+    mut keys: ResMut<Input<KeyCode>>,
+    mut gamepad_buttons: ResMut<Input<GamepadButton>>,
+    mut game_state: ResMut<State<GameState>>,
+) {
+    // Still fading in from the splash screen.
+    if attract_mode.is_none() || *game_state.current() == GameState::Splash {
+        return;
+    }
+    let interrupted = keys.get_just_pressed().next().is_some()
+        || gamepad_buttons.get_just_pressed().next().is_some();
+    // Ran out, or got to the game over screen or the ending.
+    let over = *game_state.current() != GameState::Game
+        || playback.is_none_or(|playback| playback.is_finished());
+    if interrupted || over {
+        // Otherwise the splash screen would take the same press as starting a game.
+        keys.clear();
+        gamepad_buttons.clear();
+        game_state.overwrite_replace(GameState::Splash).unwrap();
+    }
+}
+
+fn stop_attract_mode(
+    mut commands: Commands,
+    attract_mode: Option<Res<AttractMode>>,
+    mut config: ResMut<SimulationConfig>,
+    mut transition: ResMut<Transition>,
+) {
+    let Some(attract_mode) = attract_mode else {
+        return;
+    };
+    config.seed = attract_mode.seed;
+    commands.remove_resource::<AttractMode>();
+    commands.remove_resource::<Playback>();
+    // As when the game was launched, ready to fade into the first run.
+    *transition = Transition {
+        to_state: Some(GameState::Game),
+        ..default()
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::chunk::{ChunkAsteroid, ChunkPlugin};
+    use super::super::player::{PlayerPlugin, TailSegment};
+    use super::super::simulation::testing::{headless_app, play, start_game, TIMESTEP};
+    use super::super::wrap::WrapConfig;
+    use super::*;
+    use crate::input::ActionState;
+    use crate::MainCamera;
+
+    fn controls(rotation: f32, shooting: bool) -> Controls {
+        Controls {
+            rotation,
+            shooting,
+            aim: Some(Vec2::new(0.3, -1.7)),
+            ..default()
+        }
+    }
+
+    fn replay() -> Replay {
+        let mut replay = Replay::new(42, 1. / 60.);
+        for _ in 0..100 {
+            replay.push(controls(0., false));
+        }
+        replay.push(controls(0.123_456_79, true));
+        for _ in 0..20 {
+            replay.push(controls(-1., true));
+        }
+        replay
+    }
+
+    #[test]
+    fn held_controls_take_a_single_entry() {
+        let replay = replay();
+
+        assert_eq!(replay.len(), 121);
+        assert_eq!(replay.steps.len(), 3);
+    }
+
+    #[test]
+    fn round_trips_through_the_file() {
+        let dir = std::env::temp_dir().join(format!("ultradino2000-replay-{}", std::process::id()));
+        let path = dir.join(LAST_REPLAY_FILE);
+        let _ = fs::remove_dir_all(&dir);
+
+        replay().save(&path).unwrap();
+
+        // Floats must come back exactly for the run to play out the same.
+        assert_eq!(Replay::load(&path).unwrap(), replay());
+        assert!(!path.with_extension("ron.tmp").exists());
+    }
+
+    #[test]
+    fn plays_every_step_back_then_runs_out() {
+        let mut playback = Playback::new(replay());
+        let steps: Vec<Controls> = std::iter::from_fn(|| playback.next_step()).collect();
+
+        assert_eq!(steps.len(), 121);
+        assert_eq!(steps[99], controls(0., false));
+        assert_eq!(steps[100], controls(0.123_456_79, true));
+        assert_eq!(steps[120], controls(-1., true));
+
+        playback.rewind();
+        assert_eq!(playback.next_step(), Some(controls(0., false)));
+    }
+
+    /// A run of the dino and the asteroids around it, with the controls taken from `replay`.
+    fn replayed_run(replay: Replay, steps: u64) -> App {
+        let mut app = headless_app(replay.seed);
+        app.init_resource::<Windows>()
+            .init_resource::<Input<KeyCode>>()
+            .init_resource::<Input<GamepadButton>>()
+            .init_resource::<ActionState>()
+            .init_resource::<Transition>()
+            .init_resource::<WrapConfig>()
+            .insert_resource(Playback::new(replay))
+            .add_plugin(PlayerPlugin)
+            .add_plugin(ChunkPlugin)
+            .add_plugin(ReplayPlugin);
+        app.world.spawn((
+            MainCamera,
+            Transform::default(),
+            OrthographicProjection {
+                scale: 40.,
+                ..default()
+            },
+        ));
+        start_game(&mut app);
+        play(&mut app, steps, 2);
+        app
+    }
+
+    fn positions<F: bevy::ecs::query::ReadOnlyWorldQuery>(app: &mut App) -> Vec<Vec3> {
+        let mut query = app.world.query_filtered::<&Transform, F>();
+        query
+            .iter(&app.world)
+            .map(|transform| transform.translation)
+            .collect()
+    }
+
+    fn asteroid_positions(app: &mut App) -> Vec<(i32, i32, u32, Vec3)> {
+        let mut query = app.world.query::<(&ChunkAsteroid, &Transform)>();
+        let mut asteroids: Vec<_> = query
+            .iter(&app.world)
+            .map(|(asteroid, transform)| {
+                let ChunkAsteroid { chunk, index } = *asteroid;
+                (chunk.y, chunk.x, index, transform.translation)
+            })
+            .collect();
+        asteroids.sort_unstable_by_key(|&(y, x, index, _)| (y, x, index));
+        asteroids
+    }
+
+    #[test]
+    fn a_recorded_run_plays_out_the_same() {
+        const STEPS: u64 = 240;
+        // Stands in for a player: thrusting while turning one way then the other.
+        let mut script = Replay::new(5, TIMESTEP);
+        for step in 0..STEPS {
+            script.push(Controls {
+                rotation: if step % 80 < 40 { 0.6 } else { -0.4 },
+                propulsion: step % 30 < 20,
+                ..default()
+            });
+        }
+
+        let mut recorded = replayed_run(script, STEPS);
+        let replay = recorded.world.resource::<Recording>().0.clone();
+        assert_eq!(replay.len(), STEPS);
+        let mut replayed = replayed_run(replay, STEPS);
+
+        let player = positions::<With<Player>>(&mut recorded);
+        assert!(
+            player[0].length() > 10.,
+            "the dino stayed put at {}",
+            player[0]
+        );
+        assert_eq!(player, positions::<With<Player>>(&mut replayed));
+        assert_eq!(
+            positions::<With<TailSegment>>(&mut recorded),
+            positions::<With<TailSegment>>(&mut replayed)
+        );
+        let asteroids = asteroid_positions(&mut recorded);
+        assert!(!asteroids.is_empty());
+        assert_eq!(asteroids, asteroid_positions(&mut replayed));
+    }
+}
@@ -0,0 +1,81 @@
+use super::asteroid::{AsteroidDestroyed, AsteroidVaporised};
+use super::laser::LaserSystem;
+use super::monster::MonsterKilled;
+use super::simulation::{SimulationApp, SimulationClock};
+use crate::GameState;
+
+use bevy::prelude::*;
+
+const POINTS_PER_AREA: f32 = 1.;
+const POINTS_PER_FRAGMENT: u32 = 5;
+const POINTS_PER_MONSTER: u32 = 250;
+const POINTS_PER_SECOND: f32 = 1.;
+
+/// Score of the current run, itemised so the breakdown can be shown at the end.
+#[derive(Resource, Default, Debug, Clone)]
+pub struct Score {
+    pub area_destroyed: f32,
+    pub fragments_vaporised: u32,
+    pub monsters_killed: u32,
+    pub time_survived: f32,
+}
+
+impl Score {
+    pub fn area_points(&self) -> u32 {
+        (self.area_destroyed * POINTS_PER_AREA) as u32
+    }
+
+    pub fn fragment_points(&self) -> u32 {
+        self.fragments_vaporised * POINTS_PER_FRAGMENT
+    }
+
+    pub fn monster_points(&self) -> u32 {
+        self.monsters_killed * POINTS_PER_MONSTER
+    }
+
+    pub fn time_points(&self) -> u32 {
+        (self.time_survived * POINTS_PER_SECOND) as u32
+    }
+
+    pub fn total(&self) -> u32 {
+        self.area_points() + self.fragment_points() + self.monster_points() + self.time_points()
+    }
+}
+
+#[derive(SystemLabel, Debug, Clone, PartialEq, Eq, Hash)]
+pub enum ScoreSystem {
+    Update,
+}
+
+pub struct ScorePlugin;
+
+impl Plugin for ScorePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Score>()
+            .add_system_set(SystemSet::on_enter(GameState::Game).with_system(reset_score))
+            .add_simulation_system_set(
+                SystemSet::new()
+                    .with_system(score.label(ScoreSystem::Update).after(LaserSystem::Rays)),
+            );
+    }
+}
+
+fn reset_score(mut score: ResMut<Score>) {
+    *score = Score::default();
+}
+
+fn score(
+    clock: Res<SimulationClock>,
+    mut score: ResMut<Score>,
+    mut asteroid_destroyed_events: EventReader<AsteroidDestroyed>,
+    mut asteroid_vaporised_events: EventReader<AsteroidVaporised>,
+    mut monster_killed_events: EventReader<MonsterKilled>,
+) {
+    score.time_survived += clock.delta_seconds();
+    score.area_destroyed += asteroid_destroyed_events
+        .iter()
+        .map(|event| event.area)
+        .sum::<f32>();
+    score.fragments_vaporised += asteroid_vaporised_events.iter().count() as u32;
+    score.monsters_killed += monster_killed_events.iter().count() as u32;
+}
@@ -0,0 +1,441 @@
+use crate::GameState;
+
+use bevy::{ecs::schedule::ShouldRun, prelude::*};
+
+use bevy_rapier2d::prelude::*;
+
+use rand::{rngs::StdRng, Error, RngCore, SeedableRng};
+
+use std::time::Duration;
+
+// Past this many steps in a frame the simulation gives up catching up, rather than taking
+// longer and longer to run every frame.
+const MAX_STEPS_PER_FRAME: u32 = 4;
+
+/// Insert before `GamePlugin` to override the defaults.
+#[derive(Resource, Clone, Debug)]
+pub struct SimulationConfig {
+    /// Every run plays out differently unless a seed is given, e.g. with `--seed`.
+    pub seed: Option<u64>,
+    /// Seconds simulated by each step, physics included.
+    pub timestep: f32,
+}
+
+impl Default for SimulationConfig {
+    fn default() -> Self {
+        SimulationConfig {
+            seed: None,
+            timestep: 1. / 60.,
+        }
+    }
+}
+
+/// Reads `<name> <value>` or `<name>=<value>` from the command line arguments.
+pub fn arg_value<'a>(args: &'a [String], name: &str) -> Option<&'a str> {
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.strip_prefix(name) {
+            Some("") => return args.next().map(String::as_str),
+            // Anything else is another argument that merely starts the same, like `--seedling`.
+            Some(value) if value.starts_with('=') => return Some(&value[1..]),
+            _ => continue,
+        }
+    }
+    None
+}
+
+/// Reads `--seed <n>` from the command line arguments.
+pub fn seed_from_args(args: &[String]) -> Option<u64> {
+    arg_value(args, "--seed").and_then(|value| value.parse().ok())
+}
+
+/// The only source of randomness for gameplay, reseeded at the start of every run.
+#[derive(Resource)]
+pub struct GameRng {
+    seed: u64,
+    rng: StdRng,
+}
+
+impl GameRng {
+    pub fn new(seed: u64) -> Self {
+        GameRng {
+            seed,
+            rng: StdRng::seed_from_u64(seed),
+        }
+    }
+
+    /// What the current run was seeded with, to play it again.
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+}
+
+impl Default for GameRng {
+    fn default() -> Self {
+        GameRng::new(0)
+    }
+}
+
+impl RngCore for GameRng {
+    fn next_u32(&mut self) -> u32 {
+        self.rng.next_u32()
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.rng.next_u64()
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        self.rng.fill_bytes(dest)
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), Error> {
+        self.rng.try_fill_bytes(dest)
+    }
+}
+
+/// Time as seen by gameplay, which advances by whole steps whatever the frame rate.
+#[derive(Resource, Debug)]
+pub struct SimulationClock {
+    timestep: f32,
+    time_scale: f32,
+    accumulator: f32,
+    elapsed: f32,
+    step: u64,
+}
+
+impl SimulationClock {
+    pub fn new(timestep: f32) -> Self {
+        SimulationClock {
+            timestep,
+            time_scale: 1.,
+            accumulator: 0.,
+            elapsed: 0.,
+            step: 0,
+        }
+    }
+
+    /// Length of a step, in the spirit of `Time::delta`.
+    pub fn delta(&self) -> Duration {
+        Duration::from_secs_f32(self.timestep)
+    }
+
+    pub fn delta_seconds(&self) -> f32 {
+        self.timestep
+    }
+
+    /// Simulated seconds since the run started.
+    pub fn elapsed_seconds(&self) -> f32 {
+        self.elapsed
+    }
+
+    /// Steps since the run started.
+    pub fn step(&self) -> u64 {
+        self.step
+    }
+
+    /// Slows the simulation down compared to real time, without changing the steps.
+    pub fn set_time_scale(&mut self, time_scale: f32) {
+        self.time_scale = time_scale;
+    }
+
+    fn reset(&mut self) {
+        *self = SimulationClock {
+            time_scale: self.time_scale,
+            ..SimulationClock::new(self.timestep)
+        };
+    }
+
+    /// Banks a frame's worth of real time.
+    fn advance(&mut self, real_delta: f32) {
+        let max = self.timestep * MAX_STEPS_PER_FRAME as f32;
+        self.accumulator = (self.accumulator + real_delta * self.time_scale).min(max);
+    }
+
+    /// Takes a step out of the banked time, if there is enough.
+    fn next_step(&mut self) -> bool {
+        if self.accumulator < self.timestep {
+            return false;
+        }
+        self.accumulator -= self.timestep;
+        self.elapsed += self.timestep;
+        self.step += 1;
+        true
+    }
+}
+
+#[derive(StageLabel, Debug, Clone, PartialEq, Eq, Hash)]
+pub enum SimulationStage {
+    /// Runs the stages below as many times per frame as real time calls for, before
+    /// `CoreStage::Update`.
+    Fixed,
+    /// Gameplay, followed by a physics step in Rapier's own stages.
+    Update,
+}
+
+#[derive(SystemLabel, Debug, Clone, PartialEq, Eq, Hash)]
+pub enum SimulationSystem {
+    /// Anything random set up when a run starts must come after this.
+    Reset,
+}
+
+/// Gameplay systems go through this rather than `App::add_system_set`, so that they run with
+/// every simulation step instead of every frame. Steps are only taken while the game is
+/// played, so the sets need no `GameState` run criteria, which would not work outside the
+/// stage driving the state anyway.
+pub trait SimulationApp {
+    fn add_simulation_system_set(&mut self, system_set: SystemSet) -> &mut Self;
+}
+
+impl SimulationApp for App {
+    fn add_simulation_system_set(&mut self, system_set: SystemSet) -> &mut Self {
+        self.schedule
+            .stage(SimulationStage::Fixed, |schedule: &mut Schedule| {
+                schedule.add_system_set_to_stage(SimulationStage::Update, system_set)
+            });
+        self
+    }
+}
+
+pub struct SimulationPlugin;
+
+impl Plugin for SimulationPlugin {
+    fn build(&self, app: &mut App) {
+        let config = app
+            .world
+            .get_resource_or_insert_with(SimulationConfig::default)
+            .clone();
+
+        let physics = |stage| {
+            SystemStage::parallel()
+                .with_system_set(RapierPhysicsPlugin::<NoUserData>::get_systems(stage))
+        };
+        let schedule = Schedule::default()
+            .with_run_criteria(simulation_step)
+            .with_stage(SimulationStage::Update, SystemStage::parallel())
+            .with_stage(
+                PhysicsStages::SyncBackend,
+                physics(PhysicsStages::SyncBackend),
+            )
+            .with_stage(
+                PhysicsStages::StepSimulation,
+                physics(PhysicsStages::StepSimulation),
+            )
+            .with_stage(PhysicsStages::Writeback, physics(PhysicsStages::Writeback));
+
+        app.add_plugin(
+            RapierPhysicsPlugin::<NoUserData>::pixels_per_meter(10.)
+                .with_default_system_setup(false),
+        )
+        .insert_resource(RapierConfiguration {
+            gravity: Vec2::new(0., 0.),
+            timestep_mode: TimestepMode::Fixed {
+                dt: config.timestep,
+                substeps: 1,
+            },
+            ..Default::default()
+        })
+        .insert_resource(SimulationClock::new(config.timestep))
+        .init_resource::<GameRng>()
+        .add_stage_before(CoreStage::Update, SimulationStage::Fixed, schedule)
+        // Despawns are only all known at the end of the frame.
+        .add_stage_before(
+            CoreStage::Last,
+            PhysicsStages::DetectDespawn,
+            physics(PhysicsStages::DetectDespawn),
+        )
+        .add_system_set(
+            SystemSet::on_enter(GameState::Game)
+                .with_system(reset_simulation.label(SimulationSystem::Reset)),
+        );
+    }
+}
+
+fn reset_simulation(
+    config: Res<SimulationConfig>,
+    mut clock: ResMut<SimulationClock>,
+    mut rng: ResMut<GameRng>,
+) {
+    let seed = config.seed.unwrap_or_else(rand::random);
+    info!("Simulation seed: {}", seed);
+    *rng = GameRng::new(seed);
+    clock.reset();
+}
+
+/// Only the game itself is simulated; menus and pauses leave the clock stopped.
+fn simulation_step(
+    mut stepping: Local<bool>,
+    time: Res<Time>,
+    game_state: Res<State<GameState>>,
+    mut clock: ResMut<SimulationClock>,
+) -> ShouldRun {
+    // A step asking for another state must be the last one until the state changes, which
+    // happens later in the frame.
+    let state_requested = *stepping && game_state.is_changed();
+    if *game_state.current() != GameState::Game || state_requested {
+        *stepping = false;
+        return ShouldRun::No;
+    }
+    // Called again after every step of the frame, when the time was already banked.
+    if !*stepping {
+        clock.advance(time.delta_seconds());
+    }
+    *stepping = clock.next_step();
+    if *stepping {
+        ShouldRun::YesAndCheckAgain
+    } else {
+        ShouldRun::No
+    }
+}
+
+/// Windowless apps stepping the simulation, for tests across plugins.
+#[cfg(test)]
+pub mod testing {
+    use super::*;
+
+    use std::time::Instant;
+
+    // A power of two, so that frames made of whole steps add up exactly.
+    pub const TIMESTEP: f32 = 1. / 64.;
+
+    /// Still on the splash screen: add what is under test, then call `start_game`.
+    pub fn headless_app(seed: u64) -> App {
+        let mut app = App::new();
+        app.add_plugin(CorePlugin::default())
+            .add_plugin(AssetPlugin::default())
+            .add_asset::<Mesh>()
+            .add_plugin(TransformPlugin)
+            .add_plugin(HierarchyPlugin)
+            .init_resource::<Time>()
+            .insert_resource(SimulationConfig {
+                seed: Some(seed),
+                timestep: TIMESTEP,
+            })
+            .add_state(GameState::Splash)
+            .add_plugin(SimulationPlugin);
+        app
+    }
+
+    pub fn start_game(app: &mut App) {
+        app.update();
+        app.world
+            .resource_mut::<State<GameState>>()
+            .set(GameState::Game)
+            .unwrap();
+        app.update();
+    }
+
+    /// Plays frames of `steps_per_frame` steps until `steps` more were taken.
+    pub fn play(app: &mut App, steps: u64, steps_per_frame: u32) {
+        let target = app.world.resource::<SimulationClock>().step() + steps;
+        let frame = Duration::from_secs_f32(TIMESTEP * steps_per_frame as f32);
+        let mut now = app
+            .world
+            .resource::<Time>()
+            .last_update()
+            .unwrap_or_else(Instant::now);
+        while app.world.resource::<SimulationClock>().step() < target {
+            now += frame;
+            app.world.resource_mut::<Time>().update_with_instant(now);
+            app.update();
+        }
+        assert_eq!(app.world.resource::<SimulationClock>().step(), target);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::testing::*;
+    use super::*;
+
+    use rand::Rng;
+
+    fn headless_game(seed: u64) -> App {
+        let mut app = headless_app(seed);
+        app.add_simulation_system_set(SystemSet::new().with_system(nudge));
+        app.world.spawn((
+            RigidBody::Dynamic,
+            Collider::ball(1.),
+            ExternalImpulse::default(),
+            TransformBundle::default(),
+        ));
+        start_game(&mut app);
+        app
+    }
+
+    fn nudge(mut rng: ResMut<GameRng>, mut query: Query<&mut ExternalImpulse>) {
+        for mut impulse in query.iter_mut() {
+            impulse.impulse = Vec2::new(rng.gen_range(-1.0..1.0), rng.gen_range(-1.0..1.0));
+        }
+    }
+
+    fn position_after(mut app: App, steps: u64, steps_per_frame: u32) -> Vec2 {
+        play(&mut app, steps, steps_per_frame);
+        let mut query = app.world.query::<(&Transform, &RigidBody)>();
+        query.single(&app.world).0.translation.truncate()
+    }
+
+    #[test]
+    fn same_seed_and_steps_whatever_the_frame_rate() {
+        let smooth = position_after(headless_game(3), 60, 1);
+        let choppy = position_after(headless_game(3), 60, 3);
+        let other_seed = position_after(headless_game(4), 60, 1);
+
+        assert_ne!(smooth, Vec2::ZERO);
+        assert_eq!(smooth, choppy);
+        assert_ne!(smooth, other_seed);
+    }
+
+    #[test]
+    fn steps_are_taken_from_real_time() {
+        let mut clock = SimulationClock::new(0.25);
+        clock.advance(0.6);
+        assert!(clock.next_step());
+        assert!(clock.next_step());
+        assert!(!clock.next_step());
+
+        // What is left over carries on to the next frame.
+        clock.advance(0.15);
+        assert!(clock.next_step());
+        assert_eq!(clock.step(), 3);
+        assert_eq!(clock.elapsed_seconds(), 0.75);
+
+        clock.set_time_scale(0.5);
+        clock.advance(0.4);
+        assert!(!clock.next_step());
+
+        clock.set_time_scale(1.);
+        clock.advance(10.);
+        let steps = std::iter::from_fn(|| clock.next_step().then_some(())).count();
+        assert_eq!(steps, MAX_STEPS_PER_FRAME as usize);
+    }
+
+    #[test]
+    fn same_seed_same_run() {
+        let mut a = GameRng::new(42);
+        let mut b = GameRng::new(42);
+        let draws: Vec<u32> = (0..8).map(|_| a.gen()).collect();
+
+        assert_eq!(draws, (0..8).map(|_| b.gen()).collect::<Vec<u32>>());
+        assert_ne!(draws[0], GameRng::new(43).gen::<u32>());
+    }
+
+    #[test]
+    fn reads_the_seed_from_the_command_line() {
+        let args = |args: &[&str]| args.iter().map(|arg| arg.to_string()).collect::<Vec<_>>();
+
+        assert_eq!(seed_from_args(&args(&["game", "--seed", "7"])), Some(7));
+        assert_eq!(seed_from_args(&args(&["game", "--seed=12"])), Some(12));
+        assert_eq!(seed_from_args(&args(&["game", "--seed"])), None);
+        assert_eq!(seed_from_args(&args(&["game", "--seedling"])), None);
+        assert_eq!(
+            seed_from_args(&args(&["game", "--seedling", "x", "--seed", "5"])),
+            Some(5)
+        );
+        assert_eq!(seed_from_args(&args(&["game"])), None);
+        assert_eq!(
+            arg_value(&args(&["game", "--replay", "run.ron"]), "--replay"),
+            Some("run.ron")
+        );
+    }
+}
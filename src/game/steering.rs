@@ -0,0 +1,759 @@
+use super::asteroid::Asteroid;
+use super::player::{Player, PlayerSystem};
+use super::simulation::SimulationApp;
+
+use bevy::prelude::*;
+
+use bevy_rapier2d::prelude::*;
+
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+/// Position and velocity of anything a behavior can react to.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Kinematics {
+    pub position: Vec2,
+    pub velocity: Vec2,
+}
+
+/// The entity being steered, as seen by its behaviors.
+#[derive(Clone, Copy, Debug)]
+pub struct Agent {
+    pub entity: Entity,
+    pub kinematics: Kinematics,
+    pub angle: f32,
+    pub max_speed: f32,
+    pub max_force: f32,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct Obstacle {
+    pub entity: Entity,
+    pub position: Vec2,
+}
+
+/// Everything a behavior may look at during one steering update.
+pub struct SteeringContext<'a> {
+    pub agent: Agent,
+    pub collider: Option<&'a Collider>,
+    pub rapier_context: &'a RapierContext,
+    pub player: Option<Kinematics>,
+    pub neighbours: &'a [(Entity, Kinematics)],
+    pub obstacles: &'a [Obstacle],
+}
+
+impl SteeringContext<'_> {
+    pub fn resolve(&self, target: &Target) -> Option<Kinematics> {
+        match target {
+            Target::Player => self.player,
+            Target::Point(position) => Some(Kinematics {
+                position: *position,
+                velocity: Vec2::ZERO,
+            }),
+        }
+    }
+
+    /// Reynolds' steering: the force needed to go from the current to the desired velocity.
+    fn steer_towards(&self, desired_velocity: Vec2) -> Vec2 {
+        desired_velocity - self.agent.kinematics.velocity
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub enum Target {
+    Player,
+    Point(Vec2),
+}
+
+pub trait SteeringBehavior: Send + Sync {
+    fn get_steering(&mut self, _context: &SteeringContext) -> Vec2 {
+        Vec2::splat(0.)
+    }
+    fn get_angular_steering(&mut self, _context: &SteeringContext) -> f32 {
+        0.
+    }
+}
+
+/// Weighted list of behaviors blended into the entity's `ExternalForce`.
+#[derive(Component)]
+pub struct Steering {
+    behaviors: Vec<(f32, Box<dyn SteeringBehavior>)>,
+    max_speed: f32,
+    max_force: f32,
+    max_torque: f32,
+}
+
+impl Steering {
+    pub fn new(max_speed: f32, max_force: f32, max_torque: f32) -> Self {
+        Steering {
+            behaviors: Vec::new(),
+            max_speed,
+            max_force,
+            max_torque,
+        }
+    }
+
+    pub fn with(mut self, weight: f32, behavior: impl SteeringBehavior + 'static) -> Self {
+        self.behaviors.push((weight, Box::new(behavior)));
+        self
+    }
+}
+
+pub struct SeekBehavior {
+    target: Target,
+}
+
+impl SeekBehavior {
+    pub fn new(target: Target) -> Self {
+        SeekBehavior { target }
+    }
+}
+
+impl SteeringBehavior for SeekBehavior {
+    fn get_steering(&mut self, context: &SteeringContext) -> Vec2 {
+        let Some(target) = context.resolve(&self.target) else {
+            return Vec2::ZERO;
+        };
+        let desired = target.position - context.agent.kinematics.position;
+        context.steer_towards(desired.normalize_or_zero() * context.agent.max_speed)
+    }
+}
+
+pub struct FleeBehavior {
+    target: Target,
+}
+
+impl FleeBehavior {
+    pub fn new(target: Target) -> Self {
+        FleeBehavior { target }
+    }
+}
+
+impl SteeringBehavior for FleeBehavior {
+    fn get_steering(&mut self, context: &SteeringContext) -> Vec2 {
+        let Some(target) = context.resolve(&self.target) else {
+            return Vec2::ZERO;
+        };
+        let desired = context.agent.kinematics.position - target.position;
+        context.steer_towards(desired.normalize_or_zero() * context.agent.max_speed)
+    }
+}
+
+/// Seek that slows down inside `slowing_radius` to stop on the target.
+pub struct ArriveBehavior {
+    target: Target,
+    slowing_radius: f32,
+}
+
+impl ArriveBehavior {
+    pub fn new(target: Target, slowing_radius: f32) -> Self {
+        ArriveBehavior {
+            target,
+            slowing_radius,
+        }
+    }
+}
+
+impl SteeringBehavior for ArriveBehavior {
+    fn get_steering(&mut self, context: &SteeringContext) -> Vec2 {
+        let Some(target) = context.resolve(&self.target) else {
+            return Vec2::ZERO;
+        };
+        let offset = target.position - context.agent.kinematics.position;
+        let distance = offset.length();
+        let speed = context.agent.max_speed * (distance / self.slowing_radius).min(1.);
+        context.steer_towards(offset.normalize_or_zero() * speed)
+    }
+}
+
+/// Seek the position the target will have in at most `max_prediction` seconds.
+pub struct PursueBehavior {
+    target: Target,
+    max_prediction: f32,
+}
+
+impl PursueBehavior {
+    pub fn new(target: Target, max_prediction: f32) -> Self {
+        PursueBehavior {
+            target,
+            max_prediction,
+        }
+    }
+}
+
+fn predict(context: &SteeringContext, target: Kinematics, max_prediction: f32) -> Vec2 {
+    let distance = (target.position - context.agent.kinematics.position).length();
+    let prediction = (distance / context.agent.max_speed).min(max_prediction);
+    target.position + target.velocity * prediction
+}
+
+impl SteeringBehavior for PursueBehavior {
+    fn get_steering(&mut self, context: &SteeringContext) -> Vec2 {
+        let Some(target) = context.resolve(&self.target) else {
+            return Vec2::ZERO;
+        };
+        let desired =
+            predict(context, target, self.max_prediction) - context.agent.kinematics.position;
+        context.steer_towards(desired.normalize_or_zero() * context.agent.max_speed)
+    }
+}
+
+/// Flee the position the target will have in at most `max_prediction` seconds.
+pub struct EvadeBehavior {
+    target: Target,
+    max_prediction: f32,
+}
+
+impl EvadeBehavior {
+    pub fn new(target: Target, max_prediction: f32) -> Self {
+        EvadeBehavior {
+            target,
+            max_prediction,
+        }
+    }
+}
+
+impl SteeringBehavior for EvadeBehavior {
+    fn get_steering(&mut self, context: &SteeringContext) -> Vec2 {
+        let Some(target) = context.resolve(&self.target) else {
+            return Vec2::ZERO;
+        };
+        let desired =
+            context.agent.kinematics.position - predict(context, target, self.max_prediction);
+        context.steer_towards(desired.normalize_or_zero() * context.agent.max_speed)
+    }
+}
+
+/// Steers towards a point on a circle projected ahead, jittered every update.
+pub struct WanderBehavior {
+    distance: f32,
+    radius: f32,
+    jitter: f32,
+    angle: f32,
+    /// Seeded from the `GameRng` by whoever spawns the agent, so that wandering replays.
+    rng: StdRng,
+}
+
+impl WanderBehavior {
+    pub fn new(distance: f32, radius: f32, jitter: f32, seed: u64) -> Self {
+        WanderBehavior {
+            distance,
+            radius,
+            jitter,
+            angle: 0.,
+            rng: StdRng::seed_from_u64(seed),
+        }
+    }
+}
+
+impl SteeringBehavior for WanderBehavior {
+    fn get_steering(&mut self, context: &SteeringContext) -> Vec2 {
+        self.angle += self.rng.gen_range(-self.jitter..=self.jitter);
+
+        let heading = context
+            .agent
+            .kinematics
+            .velocity
+            .try_normalize()
+            .unwrap_or(Vec2::from_angle(context.agent.angle).rotate(Vec2::Y));
+        let circle_center = heading * self.distance;
+        let displacement = Vec2::from_angle(self.angle) * self.radius;
+        let desired = circle_center + displacement;
+        context.steer_towards(desired.normalize_or_zero() * context.agent.max_speed)
+    }
+}
+
+/// Casts the agent's own collider along its velocity and steers around the first asteroid it
+/// would hit within `look_ahead` seconds.
+pub struct AsteroidAvoidanceBehavior {
+    look_ahead: f32,
+}
+
+impl AsteroidAvoidanceBehavior {
+    pub fn new(look_ahead: f32) -> Self {
+        AsteroidAvoidanceBehavior { look_ahead }
+    }
+}
+
+impl SteeringBehavior for AsteroidAvoidanceBehavior {
+    fn get_steering(&mut self, context: &SteeringContext) -> Vec2 {
+        let Some(collider) = context.collider else {
+            return Vec2::ZERO;
+        };
+        let Kinematics { position, velocity } = context.agent.kinematics;
+        let Some(heading) = velocity.try_normalize() else {
+            return Vec2::ZERO;
+        };
+
+        let is_obstacle = |entity| context.obstacles.iter().any(|o| o.entity == entity);
+        let filter = QueryFilter::default()
+            .exclude_rigid_body(context.agent.entity)
+            .predicate(&is_obstacle);
+        let Some((entity, toi)) = context.rapier_context.cast_shape(
+            position,
+            context.agent.angle,
+            velocity,
+            collider,
+            self.look_ahead,
+            filter,
+        ) else {
+            return Vec2::ZERO;
+        };
+
+        let obstacle_offset = context
+            .obstacles
+            .iter()
+            .find(|obstacle| obstacle.entity == entity)
+            .map_or(heading, |obstacle| obstacle.position - position);
+
+        // The witness normals are undefined when the shapes already overlap.
+        let away = match toi.status {
+            TOIStatus::Penetrating | TOIStatus::Failed => -obstacle_offset,
+            _ => -Vec2::from_angle(context.agent.angle).rotate(toi.normal1),
+        };
+
+        // Only keep the sideways part so the agent goes around instead of braking.
+        // A head-on hit (e.g. flat faces) falls back to the side the obstacle's center is not on.
+        let away = away.normalize_or_zero();
+        let lateral = away - heading * away.dot(heading);
+        let lateral = if lateral.length_squared() > 0.01 {
+            lateral.normalize()
+        } else if obstacle_offset.perp_dot(heading) > 0. {
+            heading.perp()
+        } else {
+            -heading.perp()
+        };
+
+        let urgency = 1. - toi.toi / self.look_ahead;
+        lateral * context.agent.max_force * urgency
+    }
+}
+
+/// Keeps agents of the same kind from stacking on top of each other.
+pub struct SeparationBehavior {
+    radius: f32,
+}
+
+impl SeparationBehavior {
+    pub fn new(radius: f32) -> Self {
+        SeparationBehavior { radius }
+    }
+}
+
+impl SteeringBehavior for SeparationBehavior {
+    fn get_steering(&mut self, context: &SteeringContext) -> Vec2 {
+        let position = context.agent.kinematics.position;
+        let away: Vec2 = context
+            .neighbours
+            .iter()
+            .filter(|(entity, _)| *entity != context.agent.entity)
+            .filter_map(|(_, neighbour)| {
+                let offset = position - neighbour.position;
+                let distance = offset.length();
+                (distance > 0. && distance < self.radius).then(|| offset / (distance * distance))
+            })
+            .sum();
+        if away == Vec2::ZERO {
+            return Vec2::ZERO;
+        }
+        context.steer_towards(away.normalize() * context.agent.max_speed)
+    }
+}
+
+pub struct KeepVerticalBehavior {
+    max_speed: f32,
+}
+
+impl KeepVerticalBehavior {
+    pub fn new(max_speed: f32) -> Self {
+        KeepVerticalBehavior { max_speed }
+    }
+}
+
+impl SteeringBehavior for KeepVerticalBehavior {
+    fn get_angular_steering(&mut self, context: &SteeringContext) -> f32 {
+        if context.agent.angle < 0. {
+            self.max_speed
+        } else if context.agent.angle > 0. {
+            -self.max_speed
+        } else {
+            0.
+        }
+    }
+}
+
+#[derive(SystemLabel, Debug, Clone, PartialEq, Eq, Hash)]
+pub enum SteeringSystem {
+    Apply,
+}
+
+pub struct SteeringPlugin;
+
+impl Plugin for SteeringPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_simulation_system_set(
+            SystemSet::new().with_system(
+                apply_steering
+                    .label(SteeringSystem::Apply)
+                    .after(PlayerSystem::Movement),
+            ),
+        );
+    }
+}
+
+fn kinematics(transform: &Transform, velocity: Option<&Velocity>) -> Kinematics {
+    Kinematics {
+        position: transform.translation.truncate(),
+        velocity: velocity.map_or(Vec2::ZERO, |velocity| velocity.linvel),
+    }
+}
+
+fn apply_steering(
+    rapier_context: Res<RapierContext>,
+    mut agents_query: Query<(
+        Entity,
+        &mut Steering,
+        &mut ExternalForce,
+        &Transform,
+        Option<&Velocity>,
+        Option<&Collider>,
+    )>,
+    player_query: Query<(&Transform, Option<&Velocity>), (With<Player>, Without<Steering>)>,
+    obstacles_query: Query<(Entity, &Transform), (With<Asteroid>, Without<Steering>)>,
+) {
+    let player = player_query
+        .get_single()
+        .ok()
+        .map(|(transform, velocity)| kinematics(transform, velocity));
+
+    let neighbours: Vec<(Entity, Kinematics)> = agents_query
+        .iter()
+        .map(|(entity, _, _, transform, velocity, _)| (entity, kinematics(transform, velocity)))
+        .collect();
+
+    let obstacles: Vec<Obstacle> = obstacles_query
+        .iter()
+        .map(|(entity, transform)| Obstacle {
+            entity,
+            position: transform.translation.truncate(),
+        })
+        .collect();
+
+    for (entity, mut steering, mut force, transform, velocity, collider) in agents_query.iter_mut()
+    {
+        let steering = &mut *steering;
+        let context = SteeringContext {
+            agent: Agent {
+                entity,
+                kinematics: kinematics(transform, velocity),
+                angle: transform.rotation.to_euler(EulerRot::ZYX).0,
+                max_speed: steering.max_speed,
+                max_force: steering.max_force,
+            },
+            collider,
+            rapier_context: &rapier_context,
+            player,
+            neighbours: &neighbours,
+            obstacles: &obstacles,
+        };
+
+        let mut linear = Vec2::ZERO;
+        let mut angular = 0.;
+        for (weight, behavior) in steering.behaviors.iter_mut() {
+            linear += behavior.get_steering(&context) * *weight;
+            angular += behavior.get_angular_steering(&context) * *weight;
+        }
+
+        force.force = linear.clamp_length_max(steering.max_force);
+        force.torque = angular.clamp(-steering.max_torque, steering.max_torque);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MONSTER_SPEED: f32 = 10.;
+
+    fn headless_world() -> App {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins)
+            .add_plugin(AssetPlugin::default())
+            .add_asset::<Mesh>()
+            .add_plugin(TransformPlugin)
+            .add_plugin(HierarchyPlugin)
+            .add_plugin(RapierPhysicsPlugin::<NoUserData>::pixels_per_meter(10.))
+            .insert_resource(RapierConfiguration {
+                gravity: Vec2::ZERO,
+                ..Default::default()
+            })
+            .add_system(apply_steering);
+        app
+    }
+
+    fn spawn_monster(app: &mut App) -> Entity {
+        app.world
+            .spawn((
+                RigidBody::Dynamic,
+                Collider::cuboid(4., 3.7),
+                Velocity::linear(Vec2::new(0., MONSTER_SPEED)),
+                ExternalForce::default(),
+                Steering::new(MONSTER_SPEED, 10., 0.8).with(1., AsteroidAvoidanceBehavior::new(1.)),
+                TransformBundle::default(),
+            ))
+            .id()
+    }
+
+    fn spawn_rock(app: &mut App, position: Vec2) -> Entity {
+        app.world
+            .spawn((
+                RigidBody::Fixed,
+                Collider::ball(3.),
+                TransformBundle::from(Transform::from_translation(position.extend(0.))),
+            ))
+            .id()
+    }
+
+    fn steering_force(app: &mut App, monster: Entity) -> Vec2 {
+        // The first update registers the bodies, the second one steers with a built query pipeline.
+        app.update();
+        app.update();
+        app.world.get::<ExternalForce>(monster).unwrap().force
+    }
+
+    #[test]
+    fn steers_around_asteroid_in_path() {
+        let mut app = headless_world();
+        let monster = spawn_monster(&mut app);
+        let asteroid = spawn_rock(&mut app, Vec2::new(1., 8.));
+        app.world.entity_mut(asteroid).insert(Asteroid);
+
+        let force = steering_force(&mut app, monster);
+
+        assert!(force.x < 0., "expected to dodge left, got {force}");
+        assert!(
+            force.y.abs() < 1e-3,
+            "expected a sideways force, got {force}"
+        );
+    }
+
+    #[test]
+    fn dodges_to_the_side_opposite_the_asteroid() {
+        let mut app = headless_world();
+        let monster = spawn_monster(&mut app);
+        let asteroid = spawn_rock(&mut app, Vec2::new(-1., 8.));
+        app.world.entity_mut(asteroid).insert(Asteroid);
+
+        let force = steering_force(&mut app, monster);
+
+        assert!(force.x > 0., "expected to dodge right, got {force}");
+    }
+
+    #[test]
+    fn ignores_asteroid_outside_path() {
+        let mut app = headless_world();
+        let monster = spawn_monster(&mut app);
+        let asteroid = spawn_rock(&mut app, Vec2::new(20., 8.));
+        app.world.entity_mut(asteroid).insert(Asteroid);
+
+        assert_eq!(steering_force(&mut app, monster), Vec2::ZERO);
+    }
+
+    #[test]
+    fn ignores_colliders_that_are_not_asteroids() {
+        let mut app = headless_world();
+        let monster = spawn_monster(&mut app);
+        spawn_rock(&mut app, Vec2::new(1., 8.));
+
+        assert_eq!(steering_force(&mut app, monster), Vec2::ZERO);
+    }
+
+    fn agent(position: Vec2, velocity: Vec2) -> Agent {
+        Agent {
+            entity: Entity::from_raw(0),
+            kinematics: Kinematics { position, velocity },
+            angle: 0.,
+            max_speed: MONSTER_SPEED,
+            max_force: 5.,
+        }
+    }
+
+    /// What `behavior` asks of `agent`, with the player and the neighbours given.
+    fn steer(
+        behavior: &mut impl SteeringBehavior,
+        agent: Agent,
+        player: Option<Kinematics>,
+        neighbours: &[(Entity, Kinematics)],
+    ) -> Vec2 {
+        let rapier_context = RapierContext::default();
+        let context = SteeringContext {
+            agent,
+            collider: None,
+            rapier_context: &rapier_context,
+            player,
+            neighbours,
+            obstacles: &[],
+        };
+        behavior.get_steering(&context)
+    }
+
+    fn assert_close(actual: Vec2, expected: Vec2) {
+        assert!(
+            actual.abs_diff_eq(expected, 1e-4),
+            "expected {expected}, got {actual}"
+        );
+    }
+
+    #[test]
+    fn seek_and_flee_head_opposite_ways_at_full_speed() {
+        let target = Target::Point(Vec2::new(30., 0.));
+        // Already drifting up, which the steering has to cancel.
+        let agent = agent(Vec2::ZERO, Vec2::new(0., 2.));
+
+        let seek = steer(&mut SeekBehavior::new(target), agent, None, &[]);
+        let flee = steer(&mut FleeBehavior::new(target), agent, None, &[]);
+
+        assert_close(seek, Vec2::new(MONSTER_SPEED, -2.));
+        assert_close(flee, Vec2::new(-MONSTER_SPEED, -2.));
+    }
+
+    #[test]
+    fn arrive_slows_down_inside_its_radius() {
+        let agent = agent(Vec2::ZERO, Vec2::ZERO);
+        let arrive = |x: f32| {
+            let target = Target::Point(Vec2::new(x, 0.));
+            steer(&mut ArriveBehavior::new(target, 10.), agent, None, &[])
+        };
+
+        assert_close(arrive(40.), Vec2::new(MONSTER_SPEED, 0.));
+        assert_close(arrive(5.), Vec2::new(MONSTER_SPEED / 2., 0.));
+        assert_close(arrive(0.), Vec2::ZERO);
+    }
+
+    #[test]
+    fn pursue_and_evade_aim_where_the_player_is_going() {
+        let agent = agent(Vec2::ZERO, Vec2::ZERO);
+        // A second away at full speed, so it is predicted a second ahead, at (10, 10).
+        let player = Kinematics {
+            position: Vec2::new(MONSTER_SPEED, 0.),
+            velocity: Vec2::new(0., MONSTER_SPEED),
+        };
+        let diagonal = Vec2::ONE.normalize() * MONSTER_SPEED;
+
+        let pursue = steer(
+            &mut PursueBehavior::new(Target::Player, 2.),
+            agent,
+            Some(player),
+            &[],
+        );
+        let evade = steer(
+            &mut EvadeBehavior::new(Target::Player, 2.),
+            agent,
+            Some(player),
+            &[],
+        );
+        let short_sighted = steer(
+            &mut PursueBehavior::new(Target::Player, 0.),
+            agent,
+            Some(player),
+            &[],
+        );
+
+        assert_close(pursue, diagonal);
+        assert_close(evade, -diagonal);
+        assert_close(short_sighted, Vec2::new(MONSTER_SPEED, 0.));
+        assert_eq!(
+            steer(
+                &mut PursueBehavior::new(Target::Player, 2.),
+                agent,
+                None,
+                &[]
+            ),
+            Vec2::ZERO
+        );
+    }
+
+    #[test]
+    fn wander_is_reproducible_for_a_seed() {
+        let agent = agent(Vec2::ZERO, Vec2::new(0., 3.));
+        let wander = |seed| {
+            let mut wander = WanderBehavior::new(4., 2., 0.5, seed);
+            (0..20)
+                .map(|_| steer(&mut wander, agent, None, &[]))
+                .collect::<Vec<_>>()
+        };
+
+        let forces = wander(7);
+        assert_eq!(forces, wander(7));
+        assert_ne!(forces, wander(8));
+        for force in forces {
+            // Always wants full speed, roughly ahead.
+            let desired = force + agent.kinematics.velocity;
+            assert!((desired.length() - MONSTER_SPEED).abs() < 1e-3);
+            assert!(desired.y > 0.);
+        }
+    }
+
+    #[test]
+    fn separation_pushes_away_from_close_neighbours_only() {
+        let agent = agent(Vec2::ZERO, Vec2::ZERO);
+        let neighbour = |index, x| {
+            (
+                Entity::from_raw(index),
+                Kinematics {
+                    position: Vec2::new(x, 0.),
+                    velocity: Vec2::ZERO,
+                },
+            )
+        };
+        // The agent is among its own neighbours.
+        let close = [neighbour(0, 0.), neighbour(1, 2.), neighbour(2, 50.)];
+        let far = [neighbour(0, 0.), neighbour(2, 50.)];
+
+        let mut separation = SeparationBehavior::new(5.);
+        assert_close(
+            steer(&mut separation, agent, None, &close),
+            Vec2::new(-MONSTER_SPEED, 0.),
+        );
+        assert_eq!(steer(&mut separation, agent, None, &far), Vec2::ZERO);
+    }
+
+    #[test]
+    fn blends_behaviors_by_weight_within_the_limits() {
+        let mut app = headless_world();
+        let spawn_agent = |app: &mut App, steering: Steering| {
+            app.world
+                .spawn((
+                    steering,
+                    ExternalForce::default(),
+                    Velocity::zero(),
+                    // Tilted, for the vertical behavior to push back.
+                    TransformBundle::from(Transform::from_rotation(Quat::from_rotation_z(0.3))),
+                ))
+                .id()
+        };
+        let right = Target::Point(Vec2::new(100., 0.));
+        let up = Target::Point(Vec2::new(0., 100.));
+        let gentle = spawn_agent(
+            &mut app,
+            Steering::new(MONSTER_SPEED, 3., 0.5)
+                .with(0.2, SeekBehavior::new(right))
+                .with(0.1, SeekBehavior::new(up))
+                .with(0.1, KeepVerticalBehavior::new(2.)),
+        );
+        let strong = spawn_agent(
+            &mut app,
+            Steering::new(MONSTER_SPEED, 3., 0.5)
+                .with(1., SeekBehavior::new(right))
+                .with(1., SeekBehavior::new(right))
+                .with(1., KeepVerticalBehavior::new(2.)),
+        );
+
+        app.update();
+
+        let force = |entity| *app.world.get::<ExternalForce>(entity).unwrap();
+        assert_close(force(gentle).force, Vec2::new(2., 1.));
+        assert!((force(gentle).torque + 0.2).abs() < 1e-4);
+        assert_close(force(strong).force, Vec2::new(3., 0.));
+        assert_eq!(force(strong).torque, -0.5);
+    }
+}
@@ -0,0 +1,334 @@
+use super::asteroid::{spawn_asteroid, Asteroid, AsteroidSize};
+use super::chunk::ChunkAsteroid;
+use super::monster::{spawn_monster, Monster};
+use super::player::Player;
+use super::simulation::{GameRng, SimulationApp, SimulationClock};
+use crate::{GameState, MainCamera};
+
+use bevy::prelude::*;
+
+use bevy_rapier2d::prelude::*;
+
+use rand::{seq::SliceRandom, Rng};
+
+use std::ops::Range;
+
+// Seconds between the end of a wave and the start of the next one, and before the first.
+const WAVE_BREAK: f32 = 4.;
+// Asteroids are aimed anywhere within this distance of the player.
+const AIM_SPREAD: f32 = 15.;
+// Extra distance beyond the edge of the screen where things spawn.
+const SPAWN_MARGIN: f32 = 2.;
+// Asteroids drifting further than this from the screen are forgotten. Those generated for a
+// chunk go away with their chunk instead.
+const DESPAWN_DISTANCE: f32 = 150.;
+
+/// How much harder each wave past the last defined one gets.
+const RAMP_PER_WAVE: f32 = 0.15;
+const MIN_SPAWN_INTERVAL: f32 = 0.3;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Wave {
+    pub asteroids: u32,
+    /// Seconds between two asteroid spawns.
+    pub spawn_interval: f32,
+    /// Relative odds of each size class.
+    pub sizes: Vec<(AsteroidSize, u32)>,
+    pub speed: Range<f32>,
+    pub monsters: u32,
+}
+
+impl Wave {
+    /// This wave made harder by `steps` difficulty steps.
+    fn ramped(&self, steps: u32) -> Wave {
+        let factor = 1. + RAMP_PER_WAVE * steps as f32;
+        Wave {
+            asteroids: (self.asteroids as f32 * factor).round() as u32,
+            spawn_interval: (self.spawn_interval / factor).max(MIN_SPAWN_INTERVAL),
+            sizes: self.sizes.clone(),
+            speed: self.speed.start * factor..self.speed.end * factor,
+            monsters: self.monsters + steps / 2,
+        }
+    }
+}
+
+pub fn default_waves() -> Vec<Wave> {
+    use AsteroidSize::*;
+    vec![
+        Wave {
+            asteroids: 10,
+            spawn_interval: 2.,
+            sizes: vec![(Large, 3), (Medium, 2)],
+            speed: 1.0..3.0,
+            monsters: 0,
+        },
+        Wave {
+            asteroids: 16,
+            spawn_interval: 1.6,
+            sizes: vec![(Large, 2), (Medium, 3), (Small, 1)],
+            speed: 1.5..4.0,
+            monsters: 1,
+        },
+        Wave {
+            asteroids: 22,
+            spawn_interval: 1.3,
+            sizes: vec![(Large, 2), (Medium, 3), (Small, 3)],
+            speed: 2.0..5.0,
+            monsters: 1,
+        },
+        Wave {
+            asteroids: 28,
+            spawn_interval: 1.,
+            sizes: vec![(Large, 2), (Medium, 3), (Small, 4)],
+            speed: 2.5..6.0,
+            monsters: 2,
+        },
+        Wave {
+            asteroids: 36,
+            spawn_interval: 0.8,
+            sizes: vec![(Large, 3), (Medium, 3), (Small, 5)],
+            speed: 3.0..7.0,
+            monsters: 3,
+        },
+    ]
+}
+
+/// Spawns asteroids and monsters wave after wave. Insert before `GamePlugin` to use other
+/// waves; past the last one, it keeps repeating it with a ramped up difficulty.
+#[derive(Resource)]
+pub struct WaveDirector {
+    waves: Vec<Wave>,
+    number: u32,
+    wave: Wave,
+    asteroids_spawned: u32,
+    monsters_spawned: u32,
+    spawn_timer: Timer,
+    /// Running between two waves.
+    break_timer: Option<Timer>,
+}
+
+impl Default for WaveDirector {
+    fn default() -> Self {
+        WaveDirector::new(default_waves())
+    }
+}
+
+impl WaveDirector {
+    pub fn new(waves: Vec<Wave>) -> Self {
+        assert!(!waves.is_empty(), "at least one wave is needed");
+        let mut director = WaveDirector {
+            wave: waves[0].clone(),
+            waves,
+            number: 1,
+            asteroids_spawned: 0,
+            monsters_spawned: 0,
+            spawn_timer: Timer::default(),
+            break_timer: None,
+        };
+        director.reset();
+        director
+    }
+
+    /// The wave being played, or coming up during a break, starting from 1.
+    pub fn number(&self) -> u32 {
+        self.number
+    }
+
+    pub fn wave(&self, number: u32) -> Wave {
+        let index = (number as usize).saturating_sub(1);
+        match self.waves.get(index) {
+            Some(wave) => wave.clone(),
+            None => self.waves[self.waves.len() - 1].ramped((index + 1 - self.waves.len()) as u32),
+        }
+    }
+
+    fn reset(&mut self) {
+        self.start(1);
+        self.break_timer = Some(Timer::from_seconds(WAVE_BREAK, TimerMode::Once));
+    }
+
+    fn start(&mut self, number: u32) {
+        self.number = number;
+        self.wave = self.wave(number);
+        self.asteroids_spawned = 0;
+        self.monsters_spawned = 0;
+        self.spawn_timer = Timer::from_seconds(self.wave.spawn_interval, TimerMode::Repeating);
+        self.break_timer = None;
+    }
+
+    fn is_fully_spawned(&self) -> bool {
+        self.asteroids_spawned >= self.wave.asteroids && self.monsters_spawned >= self.wave.monsters
+    }
+
+    /// Monsters are spread evenly among the wave's asteroids.
+    fn monster_due(&self) -> bool {
+        self.monsters_spawned < self.wave.monsters
+            && self.asteroids_spawned * (self.wave.monsters + 1)
+                >= (self.monsters_spawned + 1) * self.wave.asteroids
+    }
+}
+
+#[derive(SystemLabel, Debug, Clone, PartialEq, Eq, Hash)]
+pub enum WaveSystem {
+    Direct,
+}
+
+pub struct WavePlugin;
+
+impl Plugin for WavePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<WaveDirector>()
+            .add_system_set(SystemSet::on_enter(GameState::Game).with_system(reset_waves))
+            .add_simulation_system_set(
+                SystemSet::new()
+                    .with_system(direct_waves.label(WaveSystem::Direct))
+                    .with_system(despawn_distant_asteroids),
+            );
+    }
+}
+
+fn reset_waves(mut director: ResMut<WaveDirector>) {
+    director.reset();
+}
+
+/// Half the size of the area `MainCamera` shows, in world units.
+pub fn visible_half_extents(projection: &OrthographicProjection) -> Vec2 {
+    Vec2::new(
+        projection.right - projection.left,
+        projection.top - projection.bottom,
+    ) * projection.scale
+        / 2.
+}
+
+/// A point just off screen, in a random direction from its centre.
+fn off_screen(rng: &mut impl Rng, center: Vec2, half_extents: Vec2, extent: f32) -> Vec2 {
+    let angle = rng.gen_range(0.0..std::f32::consts::TAU);
+    center + Vec2::from_angle(angle) * (half_extents.length() + extent + SPAWN_MARGIN)
+}
+
+fn direct_waves(
+    clock: Res<SimulationClock>,
+    mut rng: ResMut<GameRng>,
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut director: ResMut<WaveDirector>,
+    camera_query: Query<(&Transform, &OrthographicProjection), With<MainCamera>>,
+    player_query: Query<&Transform, With<Player>>,
+    monster_query: Query<(), With<Monster>>,
+) {
+    if let Some(break_timer) = director.break_timer.as_mut() {
+        if break_timer.tick(clock.delta()).just_finished() {
+            let next = if director.is_fully_spawned() {
+                director.number + 1
+            } else {
+                director.number
+            };
+            director.start(next);
+        }
+        return;
+    }
+
+    if director.is_fully_spawned() {
+        // The wave is over once its monsters are dead, whatever the asteroids do.
+        if monster_query.is_empty() {
+            director.break_timer = Some(Timer::from_seconds(WAVE_BREAK, TimerMode::Once));
+        }
+        return;
+    }
+
+    if !director.spawn_timer.tick(clock.delta()).just_finished() {
+        return;
+    }
+    let (Ok((camera_transform, projection)), Ok(player_transform)) =
+        (camera_query.get_single(), player_query.get_single())
+    else {
+        return;
+    };
+    let center = camera_transform.translation.truncate();
+    let half_extents = visible_half_extents(projection);
+    let player_position = player_transform.translation.truncate();
+    let rng = &mut *rng;
+
+    if director.asteroids_spawned < director.wave.asteroids {
+        let size = director
+            .wave
+            .sizes
+            .choose_weighted(rng, |(_, weight)| *weight)
+            .map(|(size, _)| *size)
+            .unwrap_or(AsteroidSize::Medium);
+        let position = off_screen(rng, center, half_extents, size.max_extent());
+        let target = player_position
+            + Vec2::from_angle(rng.gen_range(0.0..std::f32::consts::TAU))
+                * rng.gen_range(0.0..AIM_SPREAD);
+        let speed = rng.gen_range(director.wave.speed.clone());
+        let velocity = Velocity {
+            linvel: (target - position).normalize_or_zero() * speed,
+            angvel: rng.gen_range(-0.5..0.5),
+        };
+        spawn_asteroid(&mut commands, rng, size, position, velocity);
+        director.asteroids_spawned += 1;
+    }
+
+    // Monsters left over once the asteroids are all out come one per tick.
+    let asteroids_done = director.asteroids_spawned >= director.wave.asteroids;
+    if director.monster_due()
+        || (asteroids_done && director.monsters_spawned < director.wave.monsters)
+    {
+        let position = off_screen(rng, center, half_extents, 5.);
+        spawn_monster(&mut commands, &asset_server, position);
+        director.monsters_spawned += 1;
+    }
+}
+
+fn despawn_distant_asteroids(
+    mut commands: Commands,
+    camera_query: Query<&Transform, With<MainCamera>>,
+    asteroid_query: Query<(Entity, &Transform), (With<Asteroid>, Without<ChunkAsteroid>)>,
+) {
+    let Ok(camera_transform) = camera_query.get_single() else {
+        return;
+    };
+    let center = camera_transform.translation.truncate();
+    for (entity, transform) in asteroid_query.iter() {
+        if transform.translation.truncate().distance(center) > DESPAWN_DISTANCE {
+            commands.entity(entity).despawn();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ramps_difficulty_past_the_last_wave() {
+        let director = WaveDirector::default();
+        let last = default_waves().len() as u32;
+
+        assert_eq!(director.wave(1), default_waves()[0]);
+        assert_eq!(director.wave(last), director.wave(last).ramped(0));
+        let (before, after) = (director.wave(last), director.wave(last + 2));
+        assert!(after.asteroids > before.asteroids);
+        assert!(after.spawn_interval < before.spawn_interval);
+        assert!(after.speed.start > before.speed.start);
+        assert_eq!(after.monsters, before.monsters + 1);
+    }
+
+    #[test]
+    fn spreads_monsters_among_asteroids() {
+        let mut director = WaveDirector::default();
+        director.start(4);
+
+        let mut monsters_at = Vec::new();
+        while director.asteroids_spawned < director.wave.asteroids {
+            director.asteroids_spawned += 1;
+            if director.monster_due() {
+                director.monsters_spawned += 1;
+                monsters_at.push(director.asteroids_spawned);
+            }
+        }
+
+        assert_eq!(monsters_at, vec![10, 19]);
+        assert!(director.is_fully_spawned());
+    }
+}
@@ -0,0 +1,181 @@
+use super::camera::CameraSystem;
+use super::player::{Player, PlayerSystem, TailSegment};
+use super::simulation::SimulationApp;
+
+use bevy::{math::Rect, prelude::*};
+
+use bevy_rapier2d::prelude::*;
+
+// A ray longer than the arena would otherwise go round forever.
+const MAX_RAY_SEAMS: usize = 4;
+
+/// Insert before `GamePlugin` to override the defaults.
+#[derive(Resource, Default, Clone, Debug)]
+pub struct WrapConfig {
+    /// Bodies leaving this rectangle come back in from the opposite edge, like in the arcade
+    /// classic. The world is open when there is none.
+    pub arena: Option<Rect>,
+}
+
+/// Where `point` ends up once brought back inside the arena.
+pub fn wrap_point(arena: Rect, point: Vec2) -> Vec2 {
+    let size = arena.size();
+    let offset = point - arena.min;
+    arena.min + Vec2::new(offset.x.rem_euclid(size.x), offset.y.rem_euclid(size.y))
+}
+
+/// The pieces, as start point and length, of a ray crossing the arena's seams.
+pub fn wrap_ray(arena: Rect, origin: Vec2, direction: Vec2, length: f32) -> Vec<(Vec2, f32)> {
+    let mut pieces = Vec::new();
+    let mut start = wrap_point(arena, origin);
+    let mut remaining = length;
+
+    let exit_distance = |position: f32, direction: f32, min: f32, max: f32| {
+        if direction > 0. {
+            (max - position) / direction
+        } else if direction < 0. {
+            (min - position) / direction
+        } else {
+            f32::INFINITY
+        }
+    };
+
+    for _ in 0..=MAX_RAY_SEAMS {
+        let exit_x = exit_distance(start.x, direction.x, arena.min.x, arena.max.x);
+        let exit_y = exit_distance(start.y, direction.y, arena.min.y, arena.max.y);
+        let exit = exit_x.min(exit_y).max(0.);
+        if remaining <= exit {
+            pieces.push((start, remaining));
+            return pieces;
+        }
+
+        pieces.push((start, exit));
+        remaining -= exit;
+        start += direction * exit;
+        // Going out through an edge comes back in through the opposite one.
+        if exit_x <= exit {
+            start.x = if direction.x > 0. {
+                arena.min.x
+            } else {
+                arena.max.x
+            };
+        }
+        if exit_y <= exit {
+            start.y = if direction.y > 0. {
+                arena.min.y
+            } else {
+                arena.max.y
+            };
+        }
+    }
+    pieces
+}
+
+#[derive(SystemLabel, Debug, Clone, PartialEq, Eq, Hash)]
+pub enum WrapSystem {
+    Wrap,
+}
+
+pub struct WrapPlugin;
+
+impl Plugin for WrapPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<WrapConfig>().add_simulation_system_set(
+            SystemSet::new().with_system(
+                wrap_bodies
+                    .label(WrapSystem::Wrap)
+                    .after(PlayerSystem::Movement)
+                    .before(CameraSystem::Follow),
+            ),
+        );
+    }
+}
+
+fn wrap_bodies(
+    config: Res<WrapConfig>,
+    mut player_query: Query<&mut Transform, With<Player>>,
+    mut tail_query: Query<&mut Transform, (With<TailSegment>, Without<Player>)>,
+    mut body_query: Query<&mut Transform, (With<RigidBody>, Without<Player>, Without<TailSegment>)>,
+) {
+    let Some(arena) = config.arena else {
+        return;
+    };
+
+    for mut transform in body_query.iter_mut() {
+        let position = transform.translation.truncate();
+        if !arena.contains(position) {
+            let wrapped = wrap_point(arena, position);
+            transform.translation = wrapped.extend(transform.translation.z);
+        }
+    }
+
+    // The tail is jointed to the head, so it is moved along by the same amount rather than
+    // wrapped on its own, which would tear the chain apart.
+    let Ok(mut player_transform) = player_query.get_single_mut() else {
+        return;
+    };
+    let position = player_transform.translation.truncate();
+    if arena.contains(position) {
+        return;
+    }
+    let offset = (wrap_point(arena, position) - position).extend(0.);
+    player_transform.translation += offset;
+    for mut transform in tail_query.iter_mut() {
+        transform.translation += offset;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use proptest::prelude::*;
+
+    fn arena() -> Rect {
+        Rect::new(-50., -30., 50., 30.)
+    }
+
+    proptest! {
+        #[test]
+        fn wrapped_points_land_in_the_arena(x in -1000f32..1000., y in -1000f32..1000.) {
+            let point = Vec2::new(x, y);
+            let wrapped = wrap_point(arena(), point);
+
+            prop_assert!(arena().contains(wrapped));
+            let laps = (wrapped - point) / arena().size();
+            prop_assert!((laps - laps.round()).abs().max_element() < 1e-3);
+        }
+
+        #[test]
+        fn ray_pieces_add_up_to_the_ray(
+            x in -50f32..50.,
+            y in -30f32..30.,
+            angle in 0f32..std::f32::consts::TAU,
+            length in 0f32..150.,
+        ) {
+            let direction = Vec2::from_angle(angle);
+            let pieces = wrap_ray(arena(), Vec2::new(x, y), direction, length);
+
+            let total: f32 = pieces.iter().map(|(_, length)| length).sum();
+            prop_assert!(pieces.len() <= MAX_RAY_SEAMS + 1);
+            if pieces.len() <= MAX_RAY_SEAMS {
+                prop_assert!((total - length).abs() < 1e-3);
+            }
+            for (start, length) in pieces {
+                prop_assert!(arena().contains(start));
+                let end = start + direction * length;
+                prop_assert!(arena().inset(1e-3).contains(end));
+            }
+        }
+    }
+
+    #[test]
+    fn rays_come_back_through_the_opposite_edge() {
+        let pieces = wrap_ray(arena(), Vec2::new(40., 0.), Vec2::X, 30.);
+
+        assert_eq!(
+            pieces,
+            vec![(Vec2::new(40., 0.), 10.), (Vec2::new(-50., 0.), 20.)]
+        );
+    }
+}
@@ -0,0 +1,433 @@
+use bevy::prelude::*;
+
+use std::{error::Error, fmt};
+
+/// Areas below this are treated as zero.
+const EPSILON: f32 = 1e-6;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GeometryError {
+    /// A polygon needs at least three vertices, this one has the given count.
+    TooFewVertices(usize),
+    /// All the vertices are coincident or collinear.
+    ZeroArea,
+}
+
+impl fmt::Display for GeometryError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            GeometryError::TooFewVertices(count) => {
+                write!(f, "polygon has {} vertices, at least 3 are needed", count)
+            }
+            GeometryError::ZeroArea => write!(f, "polygon has no area"),
+        }
+    }
+}
+
+impl Error for GeometryError {}
+
+/// Shoelace area, positive when the vertices wind counter-clockwise.
+pub fn signed_area(vertices: &[Vec2]) -> f32 {
+    let mut sum = 0.0;
+    for i in 0..vertices.len() {
+        let v1 = vertices[i];
+        let v2 = vertices[(i + 1) % vertices.len()];
+        sum += v1.perp_dot(v2);
+    }
+    sum / 2.0
+}
+
+pub fn area(vertices: &[Vec2]) -> f32 {
+    signed_area(vertices).abs()
+}
+
+/// Fails with `ZeroArea` rather than dividing by zero on a degenerate polygon.
+pub fn centroid(vertices: &[Vec2]) -> Result<Vec2, GeometryError> {
+    if vertices.len() < 3 {
+        return Err(GeometryError::TooFewVertices(vertices.len()));
+    }
+    // Relative to the first vertex, to keep the cross products small far from the origin.
+    let origin = vertices[0];
+    let mut sum = Vec2::ZERO;
+    let mut double_area = 0.0;
+    for i in 0..vertices.len() {
+        let a = vertices[i] - origin;
+        let b = vertices[(i + 1) % vertices.len()] - origin;
+        let cross = a.perp_dot(b);
+        sum += (a + b) * cross;
+        double_area += cross;
+    }
+    if double_area.abs() / 2.0 <= EPSILON {
+        return Err(GeometryError::ZeroArea);
+    }
+    Ok(origin + sum / (3.0 * double_area))
+}
+
+/// Monotone chain hull, counter-clockwise, without collinear vertices.
+pub fn convex_hull(points: &[Vec2]) -> Result<Vec<Vec2>, GeometryError> {
+    if points.len() < 3 {
+        return Err(GeometryError::TooFewVertices(points.len()));
+    }
+    let mut sorted = points.to_vec();
+    sorted.sort_by(|a, b| a.x.total_cmp(&b.x).then(a.y.total_cmp(&b.y)));
+    sorted.dedup();
+
+    let turns_left = |hull: &[Vec2], point: Vec2| {
+        let (a, b) = (hull[hull.len() - 2], hull[hull.len() - 1]);
+        (b - a).perp_dot(point - a) > 0.
+    };
+
+    let chain = |points: &mut dyn Iterator<Item = Vec2>| {
+        let mut chain: Vec<Vec2> = Vec::with_capacity(sorted.len());
+        for point in points {
+            while chain.len() >= 2 && !turns_left(&chain, point) {
+                chain.pop();
+            }
+            chain.push(point);
+        }
+        // The last point starts the other chain.
+        chain.pop();
+        chain
+    };
+    let mut hull = chain(&mut sorted.iter().copied());
+    hull.extend(chain(&mut sorted.iter().rev().copied()));
+
+    if hull.len() < 3 || area(&hull) <= EPSILON {
+        return Err(GeometryError::ZeroArea);
+    }
+    Ok(hull)
+}
+
+/// Whether a counter-clockwise polygon has no reflex vertex; collinear vertices are allowed.
+pub fn is_convex(vertices: &[Vec2]) -> bool {
+    let n = vertices.len();
+    (0..n).all(|i| {
+        let (a, b, c) = (vertices[i], vertices[(i + 1) % n], vertices[(i + 2) % n]);
+        (b - a).perp_dot(c - b) >= 0.
+    })
+}
+
+/// Even-odd rule, so it works for any simple polygon. Points exactly on an edge may go
+/// either way.
+pub fn contains_point(vertices: &[Vec2], point: Vec2) -> bool {
+    let mut inside = false;
+    for i in 0..vertices.len() {
+        let a = vertices[i];
+        let b = vertices[(i + 1) % vertices.len()];
+        if (a.y > point.y) != (b.y > point.y) {
+            let x = a.x + (point.y - a.y) / (b.y - a.y) * (b.x - a.x);
+            if point.x < x {
+                inside = !inside;
+            }
+        }
+    }
+    inside
+}
+
+/// Keeps the part of a convex polygon on the side of the line through `point` that
+/// `normal` points to.
+pub fn clip_half_plane(vertices: &[Vec2], point: Vec2, normal: Vec2) -> Vec<Vec2> {
+    let side = |vertex: Vec2| (vertex - point).dot(normal);
+
+    let mut clipped = Vec::with_capacity(vertices.len() + 1);
+    for i in 0..vertices.len() {
+        let a = vertices[i];
+        let b = vertices[(i + 1) % vertices.len()];
+        let (side_a, side_b) = (side(a), side(b));
+
+        if side_a >= 0. {
+            clipped.push(a);
+        }
+        if (side_a > 0. && side_b < 0.) || (side_a < 0. && side_b > 0.) {
+            clipped.push(a + (b - a) * (side_a / (side_a - side_b)));
+        }
+    }
+    clipped
+}
+
+/// Splits a convex polygon along the line through `point` with the given `direction`,
+/// returning the parts on its left and on its right.
+pub fn split(vertices: &[Vec2], point: Vec2, direction: Vec2) -> (Vec<Vec2>, Vec<Vec2>) {
+    let normal = direction.perp();
+    (
+        clip_half_plane(vertices, point, normal),
+        clip_half_plane(vertices, point, -normal),
+    )
+}
+
+/// Sutherland–Hodgman: the part of `subject` inside the convex, counter-clockwise `clip`.
+pub fn clip(subject: &[Vec2], clip: &[Vec2]) -> Vec<Vec2> {
+    let mut clipped = subject.to_vec();
+    for i in 0..clip.len() {
+        if clipped.is_empty() {
+            break;
+        }
+        let a = clip[i];
+        let b = clip[(i + 1) % clip.len()];
+        clipped = clip_half_plane(&clipped, a, (b - a).perp());
+    }
+    clipped
+}
+
+/// Triangles fanned from `apex` to every edge, which covers a convex polygon when the apex
+/// is inside it.
+pub fn fan_triangles(vertices: &[Vec2], apex: Vec2) -> Vec<[Vec2; 3]> {
+    (0..vertices.len())
+        .map(|i| [apex, vertices[i], vertices[(i + 1) % vertices.len()]])
+        .collect()
+}
+
+/// Ear clipping triangulation of a simple polygon, in either winding.
+pub fn triangulate(vertices: &[Vec2]) -> Result<Vec<[Vec2; 3]>, GeometryError> {
+    if vertices.len() < 3 {
+        return Err(GeometryError::TooFewVertices(vertices.len()));
+    }
+    let winding = signed_area(vertices);
+    if winding.abs() <= EPSILON {
+        return Err(GeometryError::ZeroArea);
+    }
+
+    let mut remaining: Vec<Vec2> = vertices.to_vec();
+    if winding < 0. {
+        remaining.reverse();
+    }
+
+    let mut triangles = Vec::with_capacity(remaining.len() - 2);
+    while remaining.len() > 3 {
+        let n = remaining.len();
+        let ear = (0..n).find(|&i| {
+            let (a, b, c) = (
+                remaining[(i + n - 1) % n],
+                remaining[i],
+                remaining[(i + 1) % n],
+            );
+            let convex = (b - a).perp_dot(c - b) > 0.;
+            convex
+                && remaining
+                    .iter()
+                    .filter(|&&p| p != a && p != b && p != c)
+                    .all(|&p| !contains_point(&[a, b, c], p))
+        });
+        // Only collinear vertices are left, they don't cover any area.
+        let Some(i) = ear else {
+            break;
+        };
+        triangles.push([
+            remaining[(i + n - 1) % n],
+            remaining[i],
+            remaining[(i + 1) % n],
+        ]);
+        remaining.remove(i);
+    }
+    if remaining.len() == 3 {
+        triangles.push([remaining[0], remaining[1], remaining[2]]);
+    }
+    Ok(triangles)
+}
+
+/// Splits a simple polygon into convex parts, counter-clockwise, by merging the triangles of
+/// its triangulation for as long as the merged polygon stays convex.
+pub fn convex_decomposition(vertices: &[Vec2]) -> Result<Vec<Vec<Vec2>>, GeometryError> {
+    let mut outline = vertices.to_vec();
+    if signed_area(&outline) < 0. {
+        outline.reverse();
+    }
+    if outline.len() >= 3 && is_convex(&outline) && area(&outline) > EPSILON {
+        return Ok(vec![outline]);
+    }
+
+    let mut parts: Vec<Vec<Vec2>> = triangulate(&outline)?
+        .iter()
+        .map(|triangle| triangle.to_vec())
+        .collect();
+    while let Some((i, j, merged)) = find_convex_merge(&parts) {
+        parts[i] = merged;
+        parts.swap_remove(j);
+    }
+    Ok(parts)
+}
+
+/// Two parts sharing an edge whose union is convex, with that union.
+fn find_convex_merge(parts: &[Vec<Vec2>]) -> Option<(usize, usize, Vec<Vec2>)> {
+    for i in 0..parts.len() {
+        for j in i + 1..parts.len() {
+            let (first, second) = (&parts[i], &parts[j]);
+            for k in 0..first.len() {
+                let (a, b) = (first[k], first[(k + 1) % first.len()]);
+                // Counter-clockwise neighbours walk their shared edge in opposite directions.
+                let Some(l) = (0..second.len())
+                    .find(|&l| second[l] == b && second[(l + 1) % second.len()] == a)
+                else {
+                    continue;
+                };
+                // From b around the first part back to a, then around the second part to b.
+                let merged: Vec<Vec2> = (1..=first.len())
+                    .map(|m| first[(k + m) % first.len()])
+                    .chain((2..second.len()).map(|m| second[(l + m) % second.len()]))
+                    .collect();
+                if is_convex(&merged) {
+                    return Some((i, j, merged));
+                }
+            }
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use proptest::prelude::*;
+
+    fn points(range: f32) -> impl Strategy<Value = Vec<Vec2>> {
+        prop::collection::vec((-range..range, -range..range), 3..40)
+            .prop_map(|points| points.into_iter().map(|(x, y)| Vec2::new(x, y)).collect())
+    }
+
+    /// Star-shaped around the origin, so always simple, and concave most of the time.
+    fn star(radii: Vec<f32>) -> Vec<Vec2> {
+        let step = std::f32::consts::TAU / radii.len() as f32;
+        radii
+            .iter()
+            .enumerate()
+            .map(|(i, radius)| Vec2::from_angle(i as f32 * step) * *radius)
+            .collect()
+    }
+
+    fn close(a: f32, b: f32, scale: f32) -> bool {
+        (a - b).abs() <= scale.max(1.) * 1e-3
+    }
+
+    #[test]
+    fn rejects_degenerate_polygons() {
+        let collinear = [Vec2::ZERO, Vec2::new(1., 1.), Vec2::new(2., 2.)];
+
+        assert_eq!(
+            centroid(&[Vec2::ZERO, Vec2::X]),
+            Err(GeometryError::TooFewVertices(2))
+        );
+        assert_eq!(centroid(&collinear), Err(GeometryError::ZeroArea));
+        assert_eq!(convex_hull(&collinear), Err(GeometryError::ZeroArea));
+        assert_eq!(convex_hull(&[Vec2::ONE; 5]), Err(GeometryError::ZeroArea));
+        assert_eq!(triangulate(&collinear), Err(GeometryError::ZeroArea));
+    }
+
+    #[test]
+    fn triangulates_a_concave_polygon() {
+        let arrow = [
+            Vec2::new(0., 0.),
+            Vec2::new(4., 0.),
+            Vec2::new(4., 4.),
+            Vec2::new(2., 1.),
+            Vec2::new(0., 4.),
+        ];
+
+        let triangles = triangulate(&arrow).unwrap();
+
+        assert_eq!(triangles.len(), 3);
+        let total: f32 = triangles.iter().map(|triangle| area(triangle)).sum();
+        assert!(close(total, area(&arrow), 1.));
+        assert!(!contains_point(&arrow, Vec2::new(2., 3.)));
+        assert!(contains_point(&arrow, Vec2::new(1., 1.)));
+    }
+
+    proptest! {
+        #[test]
+        fn reversing_the_winding_flips_the_signed_area(vertices in points(10.)) {
+            let reversed: Vec<Vec2> = vertices.iter().rev().copied().collect();
+
+            prop_assert!(close(signed_area(&vertices), -signed_area(&reversed), area(&vertices)));
+        }
+
+        #[test]
+        fn hull_is_convex_and_contains_every_point(points in points(10.)) {
+            let Ok(hull) = convex_hull(&points) else {
+                return Ok(());
+            };
+
+            prop_assert!(signed_area(&hull) > 0.);
+            prop_assert!(is_convex(&hull));
+            // Shrunk towards the centroid, so points on the hull's edges count as inside.
+            let center = centroid(&hull).unwrap();
+            for point in points {
+                let point = center + (point - center) * 0.999;
+                prop_assert!(contains_point(&hull, point) || hull.contains(&point));
+            }
+        }
+
+        #[test]
+        fn centroid_is_inside_and_follows_translation(
+            points in points(10.),
+            offset in (-1000.0f32..1000.0, -1000.0f32..1000.0),
+        ) {
+            let Ok(hull) = convex_hull(&points) else {
+                return Ok(());
+            };
+            let offset = Vec2::new(offset.0, offset.1);
+            let translated: Vec<Vec2> = hull.iter().map(|&vertex| vertex + offset).collect();
+
+            let center = centroid(&hull).unwrap();
+            prop_assert!(contains_point(&hull, center));
+            let moved = centroid(&translated).unwrap() - offset;
+            prop_assert!(moved.distance(center) < 1e-2);
+        }
+
+        #[test]
+        fn triangulation_covers_the_area(points in points(10.)) {
+            let Ok(hull) = convex_hull(&points) else {
+                return Ok(());
+            };
+
+            let triangles = triangulate(&hull).unwrap();
+
+            prop_assert_eq!(triangles.len(), hull.len() - 2);
+            let total: f32 = triangles.iter().map(|triangle| area(triangle)).sum();
+            prop_assert!(close(total, area(&hull), area(&hull)));
+        }
+
+        #[test]
+        fn convex_parts_cover_the_polygon(radii in prop::collection::vec(1.0f32..10.0, 3..24)) {
+            let outline = star(radii);
+
+            let parts = convex_decomposition(&outline).unwrap();
+
+            prop_assert!(parts.len() <= outline.len() - 2);
+            for part in &parts {
+                prop_assert!(signed_area(part) > 0.);
+                prop_assert!(is_convex(part));
+            }
+            let total: f32 = parts.iter().map(|part| area(part)).sum();
+            prop_assert!(close(total, area(&outline), area(&outline)));
+        }
+
+        #[test]
+        fn split_halves_sum_to_the_whole(
+            points in points(10.),
+            point in (-10.0f32..10.0, -10.0f32..10.0),
+            angle in 0.0f32..std::f32::consts::TAU,
+        ) {
+            let Ok(hull) = convex_hull(&points) else {
+                return Ok(());
+            };
+
+            let direction = Vec2::new(angle.cos(), angle.sin());
+            let (left, right) = split(&hull, Vec2::new(point.0, point.1), direction);
+
+            prop_assert!(close(area(&left) + area(&right), area(&hull), area(&hull)));
+        }
+
+        #[test]
+        fn clipping_keeps_the_intersection(a in points(10.), b in points(10.)) {
+            let (Ok(a), Ok(b)) = (convex_hull(&a), convex_hull(&b)) else {
+                return Ok(());
+            };
+
+            let clipped = clip(&a, &b);
+
+            let clipped_area = area(&clipped);
+            prop_assert!(clipped_area <= area(&a).min(area(&b)) + 1e-3);
+            prop_assert!(close(clipped_area, area(&clip(&b, &a)), area(&a)));
+        }
+    }
+}
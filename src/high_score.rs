@@ -0,0 +1,212 @@
+use bevy::prelude::*;
+
+use directories::ProjectDirs;
+
+use serde::{Deserialize, Serialize};
+
+use std::{
+    cmp::Reverse,
+    fs,
+    io::{self, Write},
+    path::{Path, PathBuf},
+};
+
+pub const MAX_HIGH_SCORES: usize = 10;
+pub const INITIALS_LENGTH: usize = 3;
+
+const HIGH_SCORES_FILE: &str = "high_scores.ron";
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct HighScoreEntry {
+    pub initials: String,
+    pub score: u32,
+    /// Local date of the run, `YYYY-MM-DD`.
+    pub date: String,
+    /// Run duration in seconds.
+    pub duration: f32,
+}
+
+/// Best scores, highest first, persisted in the platform data directory.
+#[derive(Resource, Serialize, Deserialize, Default, Debug, PartialEq)]
+pub struct HighScores {
+    entries: Vec<HighScoreEntry>,
+}
+
+impl HighScores {
+    pub fn entries(&self) -> &[HighScoreEntry] {
+        &self.entries
+    }
+
+    pub fn qualifies(&self, score: u32) -> bool {
+        score > 0
+            && (self.entries.len() < MAX_HIGH_SCORES
+                || self.entries.iter().any(|entry| score > entry.score))
+    }
+
+    /// Inserts the entry at its rank and returns that rank, if it made it into the table.
+    pub fn insert(&mut self, entry: HighScoreEntry) -> Option<usize> {
+        let rank = self
+            .entries
+            .iter()
+            .position(|other| entry.score > other.score)
+            .unwrap_or(self.entries.len());
+        if rank >= MAX_HIGH_SCORES {
+            return None;
+        }
+        self.entries.insert(rank, entry);
+        self.entries.truncate(MAX_HIGH_SCORES);
+        Some(rank)
+    }
+
+    /// Loads the table, falling back to an empty one if the file is missing or unreadable.
+    /// A corrupted file is moved aside rather than silently overwritten by the next save.
+    pub fn load(path: &Path) -> Self {
+        let contents = match fs::read_to_string(path) {
+            Ok(contents) => contents,
+            Err(error) if error.kind() == io::ErrorKind::NotFound => return Self::default(),
+            Err(error) => {
+                warn!(
+                    "Could not read high scores from {}: {}",
+                    path.display(),
+                    error
+                );
+                return Self::default();
+            }
+        };
+
+        match ron::from_str::<HighScores>(&contents) {
+            Ok(mut high_scores) => {
+                high_scores
+                    .entries
+                    .sort_by_key(|entry| Reverse(entry.score));
+                high_scores.entries.truncate(MAX_HIGH_SCORES);
+                high_scores
+            }
+            Err(error) => {
+                let backup = path.with_extension("ron.corrupted");
+                warn!(
+                    "High scores in {} are corrupted ({}), moving them to {}",
+                    path.display(),
+                    error,
+                    backup.display()
+                );
+                if let Err(error) = fs::rename(path, &backup) {
+                    warn!("Could not move corrupted high scores: {}", error);
+                }
+                Self::default()
+            }
+        }
+    }
+
+    /// Writes to a temporary file first and renames it over the table, so a crash mid-write
+    /// never leaves a truncated file behind.
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let contents = ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())
+            .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))?;
+
+        let temporary_path = path.with_extension("ron.tmp");
+        let mut file = fs::File::create(&temporary_path)?;
+        file.write_all(contents.as_bytes())?;
+        file.sync_all()?;
+        fs::rename(&temporary_path, path)
+    }
+}
+
+pub fn high_scores_path() -> Option<PathBuf> {
+    ProjectDirs::from("", "", "ultradino2000").map(|dirs| dirs.data_dir().join(HIGH_SCORES_FILE))
+}
+
+/// Adds the entry to the table and saves it; returns its rank.
+pub fn record_high_score(high_scores: &mut HighScores, entry: HighScoreEntry) -> Option<usize> {
+    let rank = high_scores.insert(entry)?;
+    match high_scores_path() {
+        Some(path) => {
+            if let Err(error) = high_scores.save(&path) {
+                warn!(
+                    "Could not save high scores to {}: {}",
+                    path.display(),
+                    error
+                );
+            }
+        }
+        None => warn!("No data directory available, high scores won't persist"),
+    }
+    Some(rank)
+}
+
+pub fn today() -> String {
+    chrono::Local::now().format("%Y-%m-%d").to_string()
+}
+
+pub struct HighScorePlugin;
+
+impl Plugin for HighScorePlugin {
+    fn build(&self, app: &mut App) {
+        let high_scores = high_scores_path()
+            .map(|path| HighScores::load(&path))
+            .unwrap_or_default();
+        app.insert_resource(high_scores);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(initials: &str, score: u32) -> HighScoreEntry {
+        HighScoreEntry {
+            initials: initials.to_string(),
+            score,
+            date: "2023-04-21".to_string(),
+            duration: 60.,
+        }
+    }
+
+    fn temporary_path(name: &str) -> PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("ultradino2000-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        dir.join(HIGH_SCORES_FILE)
+    }
+
+    #[test]
+    fn keeps_the_best_scores_in_order() {
+        let mut high_scores = HighScores::default();
+        for score in 1..=MAX_HIGH_SCORES as u32 {
+            high_scores.insert(entry("AAA", score * 10));
+        }
+
+        assert!(!high_scores.qualifies(5));
+        assert_eq!(high_scores.insert(entry("BBB", 5)), None);
+        assert_eq!(high_scores.insert(entry("CCC", 55)), Some(5));
+        assert_eq!(high_scores.entries().len(), MAX_HIGH_SCORES);
+        assert_eq!(high_scores.entries()[0].score, 100);
+        assert_eq!(high_scores.entries().last().unwrap().score, 20);
+    }
+
+    #[test]
+    fn round_trips_through_the_file() {
+        let path = temporary_path("round-trip");
+        let mut high_scores = HighScores::default();
+        high_scores.insert(entry("DNO", 1234));
+
+        high_scores.save(&path).unwrap();
+
+        assert_eq!(HighScores::load(&path), high_scores);
+        assert!(!path.with_extension("ron.tmp").exists());
+    }
+
+    #[test]
+    fn falls_back_to_an_empty_table_on_corrupted_file() {
+        let path = temporary_path("corrupted");
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(&path, "(entries: [(initials: \"DNO\", sco").unwrap();
+
+        assert_eq!(HighScores::load(&path), HighScores::default());
+        assert!(!path.exists());
+        assert!(path.with_extension("ron.corrupted").exists());
+    }
+}
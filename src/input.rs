@@ -0,0 +1,371 @@
+use bevy::{input::InputSystem, prelude::*};
+
+use directories::ProjectDirs;
+
+use serde::{Deserialize, Serialize};
+
+use std::{
+    collections::HashMap,
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+const INPUT_MAP_FILE: &str = "input.ron";
+
+// Analog values from this far count as a press for actions that are either on or off.
+const PRESS_THRESHOLD: f32 = 0.5;
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Action {
+    RotateLeft,
+    RotateRight,
+    Thrust,
+    Fire,
+    Pause,
+    /// Saving initials, restarting and moving on from the ending.
+    Confirm,
+    /// The eyes' own stick, for the free aim mode.
+    AimLeft,
+    AimRight,
+    AimUp,
+    AimDown,
+}
+
+impl Action {
+    pub const ALL: [Action; 10] = [
+        Action::RotateLeft,
+        Action::RotateRight,
+        Action::Thrust,
+        Action::Fire,
+        Action::Pause,
+        Action::Confirm,
+        Action::AimLeft,
+        Action::AimRight,
+        Action::AimUp,
+        Action::AimDown,
+    ];
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub enum Binding {
+    Key(KeyCode),
+    GamepadButton(GamepadButtonType),
+    /// One direction of a stick, giving a value proportional to how far it is pushed.
+    GamepadAxis {
+        axis: GamepadAxisType,
+        positive: bool,
+    },
+}
+
+/// Which inputs trigger which action, read from `input.ron` in the platform config directory.
+/// Actions missing from the file are left unbound.
+#[derive(Resource, Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(default)]
+pub struct InputMap {
+    /// Stick travel ignored around the centre.
+    pub dead_zone: f32,
+    pub bindings: HashMap<Action, Vec<Binding>>,
+}
+
+impl Default for InputMap {
+    fn default() -> Self {
+        use Binding::*;
+        let stick = |axis, positive| GamepadAxis { axis, positive };
+        InputMap {
+            dead_zone: 0.15,
+            bindings: HashMap::from([
+                (
+                    Action::RotateLeft,
+                    vec![
+                        Key(KeyCode::Left),
+                        GamepadButton(GamepadButtonType::DPadLeft),
+                        stick(GamepadAxisType::LeftStickX, false),
+                    ],
+                ),
+                (
+                    Action::RotateRight,
+                    vec![
+                        Key(KeyCode::Right),
+                        GamepadButton(GamepadButtonType::DPadRight),
+                        stick(GamepadAxisType::LeftStickX, true),
+                    ],
+                ),
+                (
+                    Action::Thrust,
+                    vec![
+                        Key(KeyCode::Up),
+                        GamepadButton(GamepadButtonType::South),
+                        GamepadButton(GamepadButtonType::LeftTrigger2),
+                    ],
+                ),
+                (
+                    Action::Fire,
+                    vec![
+                        Key(KeyCode::Space),
+                        GamepadButton(GamepadButtonType::West),
+                        GamepadButton(GamepadButtonType::RightTrigger2),
+                    ],
+                ),
+                (
+                    Action::Pause,
+                    vec![Key(KeyCode::P), GamepadButton(GamepadButtonType::Start)],
+                ),
+                (
+                    Action::Confirm,
+                    vec![
+                        Key(KeyCode::Return),
+                        GamepadButton(GamepadButtonType::South),
+                    ],
+                ),
+                (
+                    Action::AimLeft,
+                    vec![stick(GamepadAxisType::RightStickX, false)],
+                ),
+                (
+                    Action::AimRight,
+                    vec![stick(GamepadAxisType::RightStickX, true)],
+                ),
+                (
+                    Action::AimUp,
+                    vec![stick(GamepadAxisType::RightStickY, true)],
+                ),
+                (
+                    Action::AimDown,
+                    vec![stick(GamepadAxisType::RightStickY, false)],
+                ),
+            ]),
+        }
+    }
+}
+
+impl InputMap {
+    /// Loads the bindings, falling back to the default ones if the file is missing or
+    /// unreadable. Unlike the high scores, a broken file is left alone for the player to fix.
+    pub fn load(path: &Path) -> Self {
+        let contents = match fs::read_to_string(path) {
+            Ok(contents) => contents,
+            Err(error) if error.kind() == io::ErrorKind::NotFound => return Self::default(),
+            Err(error) => {
+                warn!(
+                    "Could not read input map from {}: {}",
+                    path.display(),
+                    error
+                );
+                return Self::default();
+            }
+        };
+
+        ron::from_str(&contents).unwrap_or_else(|error| {
+            warn!(
+                "Input map in {} is invalid ({}), using the default bindings",
+                path.display(),
+                error
+            );
+            Self::default()
+        })
+    }
+
+    fn bindings(&self, action: Action) -> &[Binding] {
+        self.bindings.get(&action).map_or(&[], Vec::as_slice)
+    }
+}
+
+pub fn input_map_path() -> Option<PathBuf> {
+    ProjectDirs::from("", "", "ultradino2000").map(|dirs| dirs.config_dir().join(INPUT_MAP_FILE))
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+struct ActionValue {
+    value: f32,
+    pressed: bool,
+    just_pressed: bool,
+}
+
+/// Every action this frame, whatever it is bound to.
+#[derive(Resource, Default, Debug)]
+pub struct ActionState {
+    actions: HashMap<Action, ActionValue>,
+}
+
+impl ActionState {
+    /// From 0 to 1; anything but a stick gives either.
+    pub fn value(&self, action: Action) -> f32 {
+        self.get(action).value
+    }
+
+    pub fn pressed(&self, action: Action) -> bool {
+        self.get(action).pressed
+    }
+
+    /// Where the aim stick points, if it is pushed at all.
+    pub fn aim(&self) -> Option<Vec2> {
+        let aim = Vec2::new(
+            self.value(Action::AimRight) - self.value(Action::AimLeft),
+            self.value(Action::AimUp) - self.value(Action::AimDown),
+        );
+        (aim != Vec2::ZERO).then_some(aim)
+    }
+
+    pub fn just_pressed(&self, action: Action) -> bool {
+        self.get(action).just_pressed
+    }
+
+    /// Like `Input::clear_just_pressed`, so that a press is only handled once.
+    pub fn clear_just_pressed(&mut self, action: Action) {
+        if let Some(state) = self.actions.get_mut(&action) {
+            state.just_pressed = false;
+        }
+    }
+
+    fn get(&self, action: Action) -> ActionValue {
+        self.actions.get(&action).copied().unwrap_or_default()
+    }
+
+    fn set(&mut self, action: Action, value: f32) {
+        let state = self.actions.entry(action).or_default();
+        let pressed = value >= PRESS_THRESHOLD;
+        state.just_pressed = pressed && !state.pressed;
+        state.pressed = pressed;
+        state.value = value;
+    }
+}
+
+/// Rescales a stick value so that it starts from 0 at the edge of the dead zone.
+fn apply_dead_zone(value: f32, dead_zone: f32) -> f32 {
+    if value <= dead_zone {
+        0.
+    } else {
+        ((value - dead_zone) / (1. - dead_zone)).min(1.)
+    }
+}
+
+/// Where the cursor points in the world. `MainCamera` draws into an image that is stretched
+/// over the whole window, whatever its resolution, so the cursor is brought to normalized
+/// device coordinates through the window size rather than the camera's render target.
+pub fn cursor_to_world(cursor: Vec2, window_size: Vec2, ndc_to_world: Mat4) -> Vec2 {
+    let ndc = cursor / window_size * 2. - Vec2::ONE;
+    ndc_to_world.project_point3(ndc.extend(0.5)).truncate()
+}
+
+pub struct InputMapPlugin;
+
+impl Plugin for InputMapPlugin {
+    fn build(&self, app: &mut App) {
+        let input_map = input_map_path()
+            .map(|path| InputMap::load(&path))
+            .unwrap_or_default();
+        app.insert_resource(input_map)
+            .init_resource::<ActionState>()
+            .add_system_to_stage(CoreStage::PreUpdate, update_action_state.after(InputSystem));
+    }
+}
+
+fn update_action_state(
+    input_map: Res<InputMap>,
+    keys: Res<Input<KeyCode>>,
+    gamepads: Res<Gamepads>,
+    buttons: Res<Input<GamepadButton>>,
+    axes: Res<Axis<GamepadAxis>>,
+    mut action_state: ResMut<ActionState>,
+) {
+    let binding_value = |binding: &Binding| match *binding {
+        Binding::Key(key) => keys.pressed(key) as u8 as f32,
+        Binding::GamepadButton(button_type) => gamepads
+            .iter()
+            .any(|gamepad| buttons.pressed(GamepadButton::new(gamepad, button_type)))
+            as u8 as f32,
+        Binding::GamepadAxis { axis, positive } => gamepads
+            .iter()
+            .filter_map(|gamepad| axes.get(GamepadAxis::new(gamepad, axis)))
+            .map(|value| {
+                let value = if positive { value } else { -value };
+                apply_dead_zone(value, input_map.dead_zone)
+            })
+            .fold(0., f32::max),
+    };
+
+    for action in Action::ALL {
+        let value = input_map
+            .bindings(action)
+            .iter()
+            .map(binding_value)
+            .fold(0., f32::max);
+        action_state.set(action, value);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn default_bindings_round_trip_through_ron() {
+        let input_map = InputMap::default();
+        let contents = ron::to_string(&input_map).unwrap();
+
+        assert_eq!(ron::from_str::<InputMap>(&contents).unwrap(), input_map);
+        for action in Action::ALL {
+            assert!(
+                !input_map.bindings(action).is_empty(),
+                "{:?} is unbound",
+                action
+            );
+        }
+    }
+
+    #[test]
+    fn parses_a_partial_file() {
+        let input_map: InputMap = ron::from_str(
+            "(bindings: { Fire: [Key(LControl), GamepadAxis(axis: RightStickY, positive: true)] })",
+        )
+        .unwrap();
+
+        assert_eq!(input_map.dead_zone, InputMap::default().dead_zone);
+        assert_eq!(input_map.bindings(Action::Fire).len(), 2);
+        assert!(input_map.bindings(Action::Thrust).is_empty());
+    }
+
+    #[test]
+    fn reports_presses_once() {
+        let mut action_state = ActionState::default();
+        action_state.set(Action::Thrust, 0.3);
+        assert!(!action_state.pressed(Action::Thrust));
+
+        action_state.set(Action::Thrust, 0.8);
+        assert!(action_state.just_pressed(Action::Thrust));
+        action_state.set(Action::Thrust, 1.);
+        assert!(action_state.pressed(Action::Thrust));
+        assert!(!action_state.just_pressed(Action::Thrust));
+    }
+
+    #[test]
+    fn projects_the_cursor_through_the_camera() {
+        use bevy::render::camera::CameraProjection;
+
+        let projection = OrthographicProjection {
+            left: -640.,
+            right: 640.,
+            bottom: -360.,
+            top: 360.,
+            scale: 0.1,
+            ..default()
+        };
+        let camera_transform = GlobalTransform::from_xyz(10., 20., 1.);
+        let ndc_to_world =
+            camera_transform.compute_matrix() * projection.get_projection_matrix().inverse();
+        // A HiDPI window is smaller in logical pixels than the image the camera renders to.
+        let window_size = Vec2::new(640., 360.);
+
+        let center = cursor_to_world(window_size / 2., window_size, ndc_to_world);
+        let corner = cursor_to_world(window_size, window_size, ndc_to_world);
+        assert!((center - Vec2::new(10., 20.)).length() < 1e-3);
+        assert!((corner - Vec2::new(74., 56.)).length() < 1e-3);
+    }
+
+    #[test]
+    fn sticks_start_from_the_dead_zone() {
+        assert_eq!(apply_dead_zone(0.1, 0.2), 0.);
+        assert!((apply_dead_zone(0.6, 0.2) - 0.5).abs() < 1e-6);
+        assert_eq!(apply_dead_zone(1., 0.2), 1.);
+    }
+}
@@ -1,6 +1,15 @@
+// Bevy systems routinely take many parameters and nested query tuples.
+#![allow(clippy::type_complexity, clippy::too_many_arguments)]
+
 pub mod game;
+pub mod geometry;
+pub mod high_score;
+pub mod input;
 pub mod splash;
 
+use game::replay::{replay_from_args, Playback};
+use game::simulation::{seed_from_args, SimulationConfig};
+
 use bevy::{
     prelude::*,
     reflect::TypeUuid,
@@ -16,6 +25,8 @@ use bevy::{
 };
 use bevy_prototype_lyon::prelude::*;
 
+const FONT: &str = "fonts/DejaVuSansMono-Bold.ttf";
+
 #[derive(Resource)]
 struct Transition {
     is_playing: bool,
@@ -43,6 +54,10 @@ struct MainCamera;
 enum GameState {
     Splash,
     Game,
+    Paused,
+    GameOver,
+    /// The last comet hits Earth.
+    Ending,
 }
 
 #[derive(AsBindGroup, TypeUuid, Clone)]
@@ -120,6 +135,8 @@ fn setup(
             },
             ..Default::default()
         },
+        // UI is drawn by the post-processing camera unless the HUD config says otherwise.
+        UiCameraConfig { show_ui: false },
         MainCamera,
     ));
 
@@ -158,13 +175,14 @@ fn setup(
             },
             ..Camera2dBundle::default()
         },
+        UiCameraConfig::default(),
         post_processing_pass_layer,
     ));
 }
 
 fn main() {
-    App::new()
-        .insert_resource(Msaa { samples: 1 })
+    let mut app = App::new();
+    app.insert_resource(Msaa { samples: 1 })
         .insert_resource(ClearColor(Color::hex("333333").unwrap()))
         .insert_resource(Transition {
             to_state: Some(GameState::Game),
@@ -177,9 +195,27 @@ fn main() {
         .add_system(bevy::window::close_on_esc)
         .add_system(transition)
         .add_state(GameState::Splash)
+        .add_plugin(high_score::HighScorePlugin)
+        .add_plugin(input::InputMapPlugin);
+
+    // Loaded once logging is set up, so that a broken file gets reported. A replay is
+    // simulated the way it was recorded, whatever else is asked for.
+    let args: Vec<String> = std::env::args().collect();
+    let replay = replay_from_args(&args);
+    let simulation_config = match &replay {
+        Some(replay) => replay.simulation_config(),
+        None => SimulationConfig {
+            seed: seed_from_args(&args),
+            ..default()
+        },
+    };
+    app.insert_resource(simulation_config)
         .add_plugin(game::GamePlugin)
-        .add_plugin(splash::SplashPlugin)
-        .run();
+        .add_plugin(splash::SplashPlugin);
+    if let Some(replay) = replay {
+        app.insert_resource(Playback::new(replay));
+    }
+    app.run();
 }
 
 fn despawn_screen<T: Component>(to_despawn: Query<Entity, With<T>>, mut commands: Commands) {
@@ -200,7 +236,7 @@ fn transition(
     if transition.value > 1.0 || transition.value < 0.0 {
         transition.is_playing = false;
         if let Some(next_state) = transition.to_state {
-            game_state.set(next_state).unwrap();
+            game_state.replace(next_state).unwrap();
         }
     }
 
@@ -1,13 +1,20 @@
-use super::{despawn_screen, GameState, Transition};
+use super::game::replay::{attract_replay, start_attract_mode, Playback};
+use super::game::simulation::{SimulationClock, SimulationConfig};
+use super::high_score::HighScores;
+use super::{despawn_screen, GameState, MainCamera, Transition, FONT};
 
 use bevy::{
-    input::keyboard::KeyboardInput,
     prelude::*,
     reflect::TypeUuid,
     render::render_resource::{AsBindGroup, ShaderRef},
     sprite::{Material2d, Material2dPlugin, MaterialMesh2dBundle},
 };
 
+// Seconds without input before the splash screen shows the last run.
+const ATTRACT_DELAY: f32 = 20.;
+// Same as the game camera starts with.
+const SPLASH_CAMERA_SCALE: f32 = 0.1;
+
 #[derive(AsBindGroup, TypeUuid, Debug, Clone)]
 #[uuid = "f690fdae-d598-45ab-8225-97e2a3f056e0"]
 pub struct SplashMaterial {
@@ -31,7 +38,11 @@ impl Plugin for SplashPlugin {
     fn build(&self, app: &mut App) {
         app.add_plugin(Material2dPlugin::<SplashMaterial>::default())
             .add_system_set(SystemSet::on_enter(GameState::Splash).with_system(setup_splash))
-            .add_system_set(SystemSet::on_update(GameState::Splash).with_system(update))
+            .add_system_set(
+                SystemSet::on_update(GameState::Splash)
+                    .with_system(update)
+                    .with_system(attract_mode.after(update)),
+            )
             .add_system_set(
                 SystemSet::on_exit(GameState::Splash).with_system(despawn_screen::<OnSplashScreen>),
             );
@@ -47,7 +58,16 @@ fn setup_splash(
     //mut post_processing_materials: ResMut<Assets<PostProcessingMaterial>>,
     mut meshes: ResMut<Assets<Mesh>>,
     asset_server: Res<AssetServer>,
+    high_scores: Res<HighScores>,
+    mut camera_query: Query<(&mut Transform, &mut OrthographicProjection), With<MainCamera>>,
 ) {
+    // Back from attract mode the camera is wherever the run left it.
+    for (mut transform, mut projection) in camera_query.iter_mut() {
+        transform.translation = Vec3::new(0., 0., transform.translation.z);
+        transform.rotation = Quat::IDENTITY;
+        projection.scale = SPLASH_CAMERA_SCALE;
+    }
+
     let texture = asset_server.load("splash.png");
     commands.spawn((
         OnSplashScreen,
@@ -66,10 +86,52 @@ fn setup_splash(
         },
     ));
 
+    if high_scores.entries().is_empty() {
+        return;
+    }
+
+    let text_style = TextStyle {
+        font: asset_server.load(FONT),
+        font_size: 20.,
+        color: Color::WHITE,
+    };
+    let mut lines = vec!["HIGH SCORES".to_string()];
+    lines.extend(high_scores.entries().iter().enumerate().map(|(rank, entry)| {
+        format!(
+            "{:>2}. {:<3} {:>7}  {:>4.0}s  {}",
+            rank + 1,
+            entry.initials,
+            entry.score,
+            entry.duration,
+            entry.date
+        )
+    }));
+
+    commands
+        .spawn((
+            OnSplashScreen,
+            NodeBundle {
+                style: Style {
+                    size: Size::new(Val::Percent(100.), Val::Percent(100.)),
+                    flex_direction: FlexDirection::Column,
+                    justify_content: JustifyContent::FlexEnd,
+                    align_items: AlignItems::Center,
+                    padding: UiRect::bottom(Val::Px(40.)),
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+        ))
+        .with_children(|parent| {
+            for line in lines {
+                parent.spawn(TextBundle::from_section(line, text_style.clone()));
+            }
+        });
 }
 
 fn update(
-    mut key_evr: EventReader<KeyboardInput>,
+    keys: Res<Input<KeyCode>>,
+    gamepad_buttons: Res<Input<GamepadButton>>,
     mut splash_materials: ResMut<Assets<SplashMaterial>>,
     mut transition: ResMut<Transition>,
     time: Res<Time>,
@@ -79,9 +141,33 @@ fn update(
         material.time = time;
     }
 
-    for ev in key_evr.iter() {
-        match ev.state {
-            _ => transition.is_playing = true,
-        }
+    let any_button = gamepad_buttons.get_just_pressed().next().is_some();
+    if keys.get_just_pressed().next().is_some() || any_button {
+        transition.is_playing = true;
+    }
+}
+
+fn attract_mode(
+    mut commands: Commands,
+    mut idle: Local<f32>,
+    time: Res<Time>,
+    clock: Res<SimulationClock>,
+    mut config: ResMut<SimulationConfig>,
+    playback: Option<Res<Playback>>,
+    mut transition: ResMut<Transition>,
+) {
+    // A replay given on the command line already plays as soon as the game starts.
+    if transition.is_playing || playback.is_some() {
+        *idle = 0.;
+        return;
+    }
+    *idle += time.delta_seconds();
+    if *idle < ATTRACT_DELAY {
+        return;
+    }
+    *idle = 0.;
+    if let Some(replay) = attract_replay(clock.delta_seconds()) {
+        start_attract_mode(&mut commands, &mut config, replay);
+        transition.is_playing = true;
     }
 }